@@ -0,0 +1,257 @@
+//! apgcodes, the canonical object names used by Catagolue and apgsearch, such
+//! as `xs4_33` for the block or `xq4_153` for the glider.
+//!
+//! The prefix says what the object is: `xs<population>` for still lifes,
+//! `xp<period>` for oscillators and `xq<period>` for spaceships. The body is
+//! the extended Wechsler encoding of one phase: rows are cut into strips of
+//! five, each column of a strip is written as a base 32 digit with the top
+//! row as the lowest bit, and strips are separated by `z`. Runs of blank
+//! columns shorten to `w` (two), `x` (three) or `y` plus a digit (four or
+//! more). The canonical code is the shortest, then alphabetically first,
+//! encoding over every phase and all eight orientations.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::cell::Cell;
+use crate::formats::{ParseError, MAX_CELLS};
+use crate::universe::Universe;
+
+/// Longest period `encode` looks for before giving up.
+pub const MAX_PERIOD: usize = 1000;
+
+// Populations past this are treated as unbounded growth
+const MAX_POPULATION: usize = 100_000;
+
+const DIGITS: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+
+/// Returns the apgcode of `pattern`, evolved on an unbounded plane. Returns
+/// `None` unless the pattern as given recurs within `MAX_PERIOD` generations.
+pub fn encode(pattern: &Universe) -> Option<String> {
+    let mut state: HashSet<(i64, i64)> = HashSet::new();
+    for row in 0..pattern.height() {
+        for col in 0..pattern.width() {
+            if pattern.cells()[pattern.get_index(row, col)] == Cell::Alive {
+                state.insert((row as i64, col as i64));
+            }
+        }
+    }
+    if state.is_empty() {
+        return Some(String::from("xs0_0"));
+    }
+
+    let population = state.len();
+    let (start, start_offset) = normalize(&state);
+    let mut phases = vec![start.clone()];
+    let mut moved = None;
+    for _ in 0..MAX_PERIOD {
+        state = step(&state);
+        if state.is_empty() || state.len() > MAX_POPULATION {
+            return None;
+        }
+        let (phase, offset) = normalize(&state);
+        if phase == start {
+            moved = Some(offset != start_offset);
+            break;
+        }
+        phases.push(phase);
+    }
+    let moved = moved?;
+
+    let period = phases.len();
+    let prefix = if moved {
+        format!("xq{}", period)
+    } else if period == 1 {
+        format!("xs{}", population)
+    } else {
+        format!("xp{}", period)
+    };
+
+    let body = phases
+        .iter()
+        .flat_map(|phase| orientations(&to_universe(phase)))
+        .map(|orientation| wechsler(&orientation))
+        .min_by(|a, b| a.len().cmp(&b.len()).then_with(|| a.cmp(b)))
+        .unwrap();
+
+    Some(format!("{}_{}", prefix, body))
+}
+
+/// Builds the phase of the object that `code` names, cropped to its live
+/// cells. Only `xs`, `xp` and `xq` codes describe a single phase.
+pub fn decode(code: &str) -> Result<Universe, ParseError> {
+    let error = |position: usize, message: &str| ParseError::new(1, position + 1, message);
+
+    let (prefix, body) = code
+        .split_once('_')
+        .ok_or_else(|| error(0, "expected `_` after the prefix"))?;
+    let number = prefix.get(2..).unwrap_or("");
+    let known_prefix = ["xs", "xp", "xq"]
+        .iter()
+        .any(|kind| prefix.starts_with(kind));
+    if !known_prefix || number.is_empty() || !number.chars().all(|ch| ch.is_ascii_digit()) {
+        return Err(error(0, "expected an `xs`, `xp` or `xq` prefix"));
+    }
+
+    let mut alive = vec![];
+    let (mut strip, mut col) = (0u32, 0u32);
+    let (mut width, mut height) = (0u64, 0u64);
+    let mut chars = body
+        .bytes()
+        .enumerate()
+        .map(|(idx, ch)| (idx + prefix.len() + 1, ch));
+    while let Some((position, ch)) = chars.next() {
+        match ch {
+            b'z' => {
+                strip += 1;
+                col = 0;
+            }
+            b'w' => col += 2,
+            b'x' => col += 3,
+            b'y' => match chars.next() {
+                Some((_, run)) if DIGITS.contains(&run) => {
+                    col += 4 + DIGITS.iter().position(|&digit| digit == run).unwrap() as u32;
+                }
+                _ => return Err(error(position, "expected a digit after `y`")),
+            },
+            b'0'..=b'9' | b'a'..=b'v' => {
+                let value = DIGITS.iter().position(|&digit| digit == ch).unwrap();
+                for bit in 0..5 {
+                    if value & (1 << bit) != 0 {
+                        alive.push((strip * 5 + bit, col));
+                    }
+                }
+                col += 1;
+            }
+            _ => {
+                return Err(error(
+                    position,
+                    &format!("unexpected character '{}'", ch as char),
+                ))
+            }
+        }
+
+        // Blank strips and columns cost nothing to decode, so the size is
+        // checked as it grows rather than once the universe is allocated
+        if let Some(&(row, column)) = alive.last() {
+            width = width.max(column as u64 + 1);
+            height = height.max(row as u64 + 1);
+        }
+        if strip as u64 * 5 > MAX_CELLS || col as u64 > MAX_CELLS || width * height > MAX_CELLS {
+            return Err(error(position, "pattern is too large"));
+        }
+    }
+
+    let mut universe = Universe::empty(width as u32, height as u32);
+    universe.set_cells(&alive);
+    Ok(universe.crop())
+}
+
+// One generation of B3/S23 on an unbounded plane of (row, column) cells
+fn step(cells: &HashSet<(i64, i64)>) -> HashSet<(i64, i64)> {
+    let mut neighbors: HashMap<(i64, i64), u8> = HashMap::new();
+    for &(row, col) in cells.iter() {
+        for d_row in -1..=1 {
+            for d_col in -1..=1 {
+                if (d_row, d_col) != (0, 0) {
+                    *neighbors.entry((row + d_row, col + d_col)).or_insert(0) += 1;
+                }
+            }
+        }
+    }
+
+    neighbors
+        .into_iter()
+        .filter(|&(cell, count)| count == 3 || (count == 2 && cells.contains(&cell)))
+        .map(|(cell, _)| cell)
+        .collect()
+}
+
+// Sorted cells shifted to the origin, with the shift that was removed
+fn normalize(cells: &HashSet<(i64, i64)>) -> (Vec<(i64, i64)>, (i64, i64)) {
+    let top = cells.iter().map(|&(row, _)| row).min().unwrap_or(0);
+    let left = cells.iter().map(|&(_, col)| col).min().unwrap_or(0);
+    let mut shifted: Vec<(i64, i64)> = cells
+        .iter()
+        .map(|&(row, col)| (row - top, col - left))
+        .collect();
+    shifted.sort_unstable();
+    (shifted, (top, left))
+}
+
+fn to_universe(cells: &[(i64, i64)]) -> Universe {
+    let height = cells.iter().map(|&(row, _)| row + 1).max().unwrap_or(0);
+    let width = cells.iter().map(|&(_, col)| col + 1).max().unwrap_or(0);
+    let mut universe = Universe::empty(width as u32, height as u32);
+    let alive: Vec<(u32, u32)> = cells
+        .iter()
+        .map(|&(row, col)| (row as u32, col as u32))
+        .collect();
+    universe.set_cells(&alive);
+    universe
+}
+
+/// All eight rotations and reflections of `pattern`.
+pub fn orientations(pattern: &Universe) -> [Universe; 8] {
+    let flipped = pattern.flip_horizontal();
+    [
+        pattern.rotate_90(),
+        pattern.rotate_180(),
+        pattern.rotate_270(),
+        flipped.rotate_90(),
+        flipped.rotate_180(),
+        flipped.rotate_270(),
+        flipped,
+        pattern.clone(),
+    ]
+}
+
+// Extended Wechsler encoding of a cropped pattern
+fn wechsler(pattern: &Universe) -> String {
+    let mut code = String::new();
+    let strips = pattern.height().div_ceil(5);
+
+    for strip in 0..strips {
+        if strip > 0 {
+            code.push('z');
+        }
+        let mut blanks = 0;
+        for col in 0..pattern.width() {
+            let mut value = 0;
+            for bit in 0..5 {
+                let row = strip * 5 + bit;
+                if row < pattern.height()
+                    && pattern.cells()[pattern.get_index(row, col)] == Cell::Alive
+                {
+                    value |= 1 << bit;
+                }
+            }
+            if value == 0 {
+                blanks += 1;
+                continue;
+            }
+            push_blanks(&mut code, blanks);
+            blanks = 0;
+            code.push(DIGITS[value] as char);
+        }
+    }
+
+    code
+}
+
+fn push_blanks(code: &mut String, mut blanks: usize) {
+    // `yz` is the longest single run, 39 columns
+    while blanks >= 40 {
+        code.push_str("yz");
+        blanks -= 39;
+    }
+    match blanks {
+        0 => {}
+        1 => code.push('0'),
+        2 => code.push('w'),
+        3 => code.push('x'),
+        _ => {
+            code.push('y');
+            code.push(DIGITS[blanks - 4] as char);
+        }
+    }
+}
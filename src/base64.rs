@@ -0,0 +1,35 @@
+//! Unpadded URL-safe base64, for putting bytes in links and `localStorage`.
+
+const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+pub fn encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group = chunk.iter().enumerate().fold(0u32, |acc, (idx, &byte)| {
+            acc | (byte as u32) << (16 - idx * 8)
+        });
+        // Three bytes make four characters, fewer bytes one less each
+        for idx in 0..=chunk.len() {
+            out.push(ALPHABET[(group >> (18 - idx * 6) & 0x3F) as usize] as char);
+        }
+    }
+    out
+}
+
+pub fn decode(text: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(text.len() / 4 * 3);
+    for chunk in text.as_bytes().chunks(4) {
+        if chunk.len() == 1 {
+            return None;
+        }
+        let mut group = 0u32;
+        for (idx, &ch) in chunk.iter().enumerate() {
+            let value = ALPHABET.iter().position(|&digit| digit == ch)? as u32;
+            group |= value << (18 - idx * 6);
+        }
+        for idx in 0..chunk.len() - 1 {
+            out.push((group >> (16 - idx * 8)) as u8);
+        }
+    }
+    Some(out)
+}
@@ -1,5 +1,5 @@
 #[repr(u8)] // Represent Cells as single byte
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum Cell {
     Dead = 0,
     Alive = 1,
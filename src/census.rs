@@ -0,0 +1,214 @@
+//! Splitting a settled board into separate objects and naming each one, like
+//! the census apgsearch takes of a soup.
+//!
+//! Live cells within `PADDING` cells of each other, across the board's
+//! wrapped edges too, belong to the same object. The padding keeps
+//! pseudo-objects such as a bi-block together instead of counting their
+//! parts. Each object is identified by its apgcode and, when it is a common
+//! one, a name from `KNOWN_OBJECTS`.
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Write;
+
+use crate::apgcode;
+use crate::cell::Cell;
+use crate::universe::{Region, Universe};
+
+/// Largest gap, in cells, between two cells of the same object.
+pub const PADDING: u32 = 2;
+
+/// Names of common objects, by apgcode.
+pub const KNOWN_OBJECTS: &[(&str, &str)] = &[
+    ("xs4_33", "block"),
+    ("xs6_696", "beehive"),
+    ("xs7_2596", "loaf"),
+    ("xs5_253", "boat"),
+    ("xs6_356", "ship"),
+    ("xs4_252", "tub"),
+    ("xs8_6996", "pond"),
+    ("xs6_25a4", "barge"),
+    ("xs7_25ac", "long boat"),
+    ("xs6_bd", "snake"),
+    ("xs7_178c", "eater 1"),
+    ("xs12_g8o653z11", "ship-tie"),
+    ("xp2_7", "blinker"),
+    ("xp2_7e", "toad"),
+    ("xp2_318c", "beacon"),
+    ("xp3_co9nas0san9oczgoldlo0oldlogz1047210127401", "pulsar"),
+    ("xp15_4r4z4r4", "pentadecathlon"),
+    ("xq4_153", "glider"),
+    ("xq4_6frc", "lightweight spaceship"),
+    ("xq4_27dee6", "middleweight spaceship"),
+    ("xq4_27deee6", "heavyweight spaceship"),
+];
+
+/// The common name of the object with apgcode `code`.
+pub fn name(code: &str) -> Option<&'static str> {
+    KNOWN_OBJECTS
+        .iter()
+        .find(|&&(known, _)| known == code)
+        .map(|&(_, name)| name)
+}
+
+/// One object found on the board.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CensusObject {
+    /// Smallest region holding the object. It may wrap around the edges.
+    pub region: Region,
+    pub population: u64,
+    /// `None` when the object doesn't settle within `apgcode::MAX_PERIOD`.
+    pub apgcode: Option<String>,
+    pub name: Option<&'static str>,
+}
+
+/// How many objects of one kind were found.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CensusEntry {
+    pub apgcode: Option<String>,
+    pub name: Option<&'static str>,
+    pub count: usize,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Census {
+    /// Objects in the order their first cell appears, row by row.
+    pub objects: Vec<CensusObject>,
+}
+
+impl Census {
+    /// Takes the census of `universe` as it stands, without evolving it.
+    pub fn take(universe: &Universe) -> Census {
+        // apgcodes already worked out, keyed by the object's cells
+        let mut codes: HashMap<Vec<(u32, u32)>, Option<String>> = HashMap::new();
+        let objects = segment(universe)
+            .into_iter()
+            .map(|cells| {
+                let top = cells.iter().map(|&(row, _)| row).min().unwrap();
+                let left = cells.iter().map(|&(_, col)| col).min().unwrap();
+                let bottom = cells.iter().map(|&(row, _)| row).max().unwrap();
+                let right = cells.iter().map(|&(_, col)| col).max().unwrap();
+                let mut shifted: Vec<(u32, u32)> = cells
+                    .iter()
+                    .map(|&(row, col)| ((row - top) as u32, (col - left) as u32))
+                    .collect();
+                shifted.sort_unstable();
+
+                let (height, width) = ((bottom - top + 1) as u32, (right - left + 1) as u32);
+                let apgcode = codes
+                    .entry(shifted.clone())
+                    .or_insert_with(|| {
+                        let mut object = Universe::empty(width, height);
+                        object.set_cells(&shifted);
+                        apgcode::encode(&object)
+                    })
+                    .clone();
+
+                CensusObject {
+                    region: Region {
+                        row: top.rem_euclid(universe.height() as i64) as u32,
+                        column: left.rem_euclid(universe.width() as i64) as u32,
+                        height,
+                        width,
+                    },
+                    population: cells.len() as u64,
+                    name: apgcode.as_deref().and_then(name),
+                    apgcode,
+                }
+            })
+            .collect();
+
+        Census { objects }
+    }
+
+    /// Counts of each kind of object, most common first.
+    pub fn entries(&self) -> Vec<CensusEntry> {
+        let mut entries: Vec<CensusEntry> = vec![];
+        for object in self.objects.iter() {
+            match entries
+                .iter_mut()
+                .find(|entry| entry.apgcode == object.apgcode)
+            {
+                Some(entry) => entry.count += 1,
+                None => entries.push(CensusEntry {
+                    apgcode: object.apgcode.clone(),
+                    name: object.name,
+                    count: 1,
+                }),
+            }
+        }
+        // Unidentified objects go last
+        entries.sort_by(|a, b| {
+            b.count
+                .cmp(&a.count)
+                .then_with(|| a.apgcode.is_none().cmp(&b.apgcode.is_none()))
+                .then_with(|| a.apgcode.cmp(&b.apgcode))
+        });
+        entries
+    }
+
+    /// The counts as tab-separated `count`, `apgcode` and `name` columns
+    /// under a header line. Unidentified objects have a `-` apgcode and
+    /// unnamed ones an empty name.
+    pub fn report(&self) -> String {
+        let mut report = String::from("count\tapgcode\tname\n");
+        for entry in self.entries() {
+            writeln!(
+                report,
+                "{}\t{}\t{}",
+                entry.count,
+                entry.apgcode.as_deref().unwrap_or("-"),
+                entry.name.unwrap_or("")
+            )
+            .unwrap();
+        }
+        report
+    }
+}
+
+// Groups live cells into objects, each a list of (row, column) cells with
+// coordinates unwrapped so an object crossing an edge stays in one piece
+fn segment(universe: &Universe) -> Vec<Vec<(i64, i64)>> {
+    let (width, height) = (universe.width() as i64, universe.height() as i64);
+    let padding = PADDING as i64;
+    let mut visited = vec![false; universe.cells().len()];
+    let mut objects = vec![];
+
+    for start in 0..universe.cells().len() {
+        if visited[start] || universe.cells()[start] != Cell::Alive {
+            continue;
+        }
+        visited[start] = true;
+        let mut cells = vec![];
+        let mut queue = VecDeque::new();
+        queue.push_back((start as i64 / width, start as i64 % width));
+        while let Some((row, col)) = queue.pop_front() {
+            cells.push((row, col));
+            for d_row in -padding..=padding {
+                for d_col in -padding..=padding {
+                    let (next_row, next_col) = (row + d_row, col + d_col);
+                    let idx = universe.get_index(
+                        next_row.rem_euclid(height) as u32,
+                        next_col.rem_euclid(width) as u32,
+                    );
+                    if !visited[idx] && universe.cells()[idx] == Cell::Alive {
+                        visited[idx] = true;
+                        queue.push_back((next_row, next_col));
+                    }
+                }
+            }
+        }
+        objects.push(cells);
+    }
+
+    objects
+}
+
+impl Universe {
+    /// Takes a census of the objects on the board.
+    pub fn census(&self) -> Census {
+        Census::take(self)
+    }
+}
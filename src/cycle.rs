@@ -0,0 +1,320 @@
+//! Noticing when the board settles into a repeating cycle.
+//!
+//! The detector remembers a hash of the shape of each recent generation,
+//! cropped to its live cells, and where on the board that shape was. When a
+//! new generation has the same shape as an earlier one, the board has
+//! entered a cycle whose period is the number of generations between them.
+//! If the shape has moved, the cycle is a spaceship and the move is its
+//! displacement.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+use crate::cell::Cell;
+use crate::universe::{Region, Universe};
+
+/// Generations remembered by `CycleDetector::default`, and so the longest
+/// period it can find.
+pub const DEFAULT_HISTORY: usize = 1024;
+
+/// A repeating sequence of generations.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Cycle {
+    /// First generation of the cycle.
+    pub start: u64,
+    pub period: u64,
+    /// Live cells when the repeat was found.
+    pub population: u64,
+    /// Columns right and rows down the board moved over one period. Moves
+    /// across an edge take the shorter way around the torus.
+    pub displacement: (i32, i32),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CycleKind {
+    Dead,
+    StillLife,
+    Oscillator,
+    Spaceship,
+}
+
+impl Cycle {
+    pub fn kind(&self) -> CycleKind {
+        match (self.population, self.period) {
+            (0, _) => CycleKind::Dead,
+            _ if self.displacement != (0, 0) => CycleKind::Spaceship,
+            (_, 1) => CycleKind::StillLife,
+            _ => CycleKind::Oscillator,
+        }
+    }
+
+    /// How fast and which way the board moves, for spaceships.
+    pub fn velocity(&self) -> Option<Velocity> {
+        if self.kind() != CycleKind::Spaceship {
+            return None;
+        }
+        Some(Velocity {
+            dx: self.displacement.0,
+            dy: self.displacement.1,
+            period: self.period,
+        })
+    }
+}
+
+impl fmt::Display for Cycle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.kind() {
+            CycleKind::Dead => write!(f, "dead"),
+            CycleKind::StillLife => write!(f, "still life"),
+            CycleKind::Oscillator => write!(f, "period-{} oscillator", self.period),
+            CycleKind::Spaceship => write!(f, "{} spaceship", self.velocity().unwrap()),
+        }
+    }
+}
+
+/// A displacement of (`dx`, `dy`) cells every `period` generations, with
+/// `dy` counting rows down the board.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Velocity {
+    pub dx: i32,
+    pub dy: i32,
+    pub period: u64,
+}
+
+impl Velocity {
+    /// "orthogonal", "diagonal" or "oblique".
+    pub fn direction(&self) -> &'static str {
+        let (dx, dy) = (self.dx.unsigned_abs(), self.dy.unsigned_abs());
+        if dx == 0 || dy == 0 {
+            "orthogonal"
+        } else if dx == dy {
+            "diagonal"
+        } else {
+            "oblique"
+        }
+    }
+
+    /// Compass heading with north up the board, such as "south-east" or,
+    /// for oblique moves, "east-north-east".
+    pub fn heading(&self) -> String {
+        let vertical = match self.dy.signum() {
+            -1 => "north",
+            1 => "south",
+            _ => "",
+        };
+        let horizontal = match self.dx.signum() {
+            -1 => "west",
+            1 => "east",
+            _ => "",
+        };
+        let (dx, dy) = (self.dx.unsigned_abs(), self.dy.unsigned_abs());
+        if dx == 0 {
+            vertical.to_string()
+        } else if dy == 0 {
+            horizontal.to_string()
+        } else if dx == dy {
+            format!("{}-{}", vertical, horizontal)
+        } else if dx > dy {
+            format!("{}-{}-{}", horizontal, vertical, horizontal)
+        } else {
+            format!("{}-{}-{}", vertical, vertical, horizontal)
+        }
+    }
+}
+
+/// Written in the usual notation, with `c` for one cell per generation:
+/// `c/4 diagonal`, `2c/5 orthogonal` or `(2,1)c/6 oblique`.
+impl fmt::Display for Velocity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (dx, dy) = (self.dx.unsigned_abs() as u64, self.dy.unsigned_abs() as u64);
+        let (long, short) = (dx.max(dy), dx.min(dy));
+        let divisor = gcd(gcd(long, short), self.period);
+        let (long, short, period) = (long / divisor, short / divisor, self.period / divisor);
+
+        match self.direction() {
+            "oblique" => write!(f, "({},{})c", long, short)?,
+            _ if long == 1 => write!(f, "c")?,
+            _ => write!(f, "{}c", long)?,
+        }
+        if period != 1 {
+            write!(f, "/{}", period)?;
+        }
+        write!(f, " {}", self.direction())
+    }
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CycleDetector {
+    history: usize,
+    // Generation and board position each remembered shape was first seen at
+    seen: HashMap<u64, (u64, (u32, u32))>,
+    // Remembered states, oldest first
+    order: VecDeque<u64>,
+    // (generation, hash) of the last state observed
+    last: Option<(u64, u64)>,
+    found: Option<Cycle>,
+}
+
+impl CycleDetector {
+    /// A detector remembering the last `history` generations.
+    pub fn new(history: usize) -> CycleDetector {
+        CycleDetector {
+            history: history.max(1),
+            seen: HashMap::new(),
+            order: VecDeque::new(),
+            last: None,
+            found: None,
+        }
+    }
+
+    /// Forgets every remembered generation and any cycle found.
+    pub fn reset(&mut self) {
+        self.seen.clear();
+        self.order.clear();
+        self.last = None;
+        self.found = None;
+    }
+
+    /// The cycle found since the last reset, if any.
+    pub fn cycle(&self) -> Option<Cycle> {
+        self.found
+    }
+
+    /// Records the current generation of `universe`. Returns the cycle the
+    /// first time a generation repeats; after that the detector stays quiet
+    /// until the board is edited.
+    ///
+    /// A board that changes without its generation advancing, or that jumps
+    /// generations, was edited or replaced, so the history starts over.
+    /// Observing the same state twice in a row is harmless.
+    pub fn observe(&mut self, universe: &Universe) -> Option<Cycle> {
+        let generation = universe.generation();
+        let (shape, origin) = shape_hash(universe);
+        let hash = {
+            let mut hasher = DefaultHasher::new();
+            (shape, origin).hash(&mut hasher);
+            hasher.finish()
+        };
+        match self.last {
+            Some(last) if last == (generation, hash) => return None,
+            Some((last_generation, _)) if generation == last_generation + 1 => {}
+            Some(_) => self.reset(),
+            None => {}
+        }
+        self.last = Some((generation, hash));
+
+        if self.found.is_some() {
+            return None;
+        }
+        if let Some(&(start, start_origin)) = self.seen.get(&shape) {
+            let wrap = |from: u32, to: u32, size: u32| {
+                let ahead = (to + size - from) % size;
+                if ahead > size / 2 {
+                    ahead as i32 - size as i32
+                } else {
+                    ahead as i32
+                }
+            };
+            self.found = Some(Cycle {
+                start,
+                period: generation - start,
+                population: universe.population(),
+                displacement: (
+                    wrap(start_origin.1, origin.1, universe.width()),
+                    wrap(start_origin.0, origin.0, universe.height()),
+                ),
+            });
+            return self.found;
+        }
+
+        self.seen.insert(shape, (generation, origin));
+        self.order.push_back(shape);
+        if self.order.len() > self.history {
+            if let Some(oldest) = self.order.pop_front() {
+                self.seen.remove(&oldest);
+            }
+        }
+        None
+    }
+}
+
+impl Default for CycleDetector {
+    fn default() -> CycleDetector {
+        CycleDetector::new(DEFAULT_HISTORY)
+    }
+}
+
+// Hashes the board's live cells cropped to their bounds, along with the
+// (row, column) the bounds start at
+fn shape_hash(universe: &Universe) -> (u64, (u32, u32)) {
+    let mut hasher = DefaultHasher::new();
+    universe.width().hash(&mut hasher);
+    universe.height().hash(&mut hasher);
+    match torus_bounds(universe) {
+        Some(bounds) => {
+            (bounds.width, bounds.height).hash(&mut hasher);
+            universe.copy_region(bounds).cells().hash(&mut hasher);
+            (hasher.finish(), (bounds.row, bounds.column))
+        }
+        None => (hasher.finish(), (0, 0)),
+    }
+}
+
+// The smallest region, possibly wrapping around the edges, holding every
+// live cell. Each axis skips the longest run of empty lines, counting runs
+// that continue past the edge.
+fn torus_bounds(universe: &Universe) -> Option<Region> {
+    let (width, height) = (universe.width(), universe.height());
+    let mut rows = vec![false; height as usize];
+    let mut cols = vec![false; width as usize];
+    for row in 0..height {
+        for col in 0..width {
+            if universe.cells()[universe.get_index(row, col)] == Cell::Alive {
+                rows[row as usize] = true;
+                cols[col as usize] = true;
+            }
+        }
+    }
+    let (row, height) = occupied_span(&rows)?;
+    let (column, width) = occupied_span(&cols)?;
+    Some(Region {
+        row,
+        column,
+        height,
+        width,
+    })
+}
+
+// (start, length) of the shortest cyclic span holding every occupied line
+fn occupied_span(occupied: &[bool]) -> Option<(u32, u32)> {
+    let size = occupied.len();
+    let first = occupied.iter().position(|&line| line)?;
+    // Walk once around from the first occupied line, measuring the gaps
+    let (mut gap_start, mut gap) = (0, 0);
+    let mut run = 0;
+    for step in 1..=size {
+        if occupied[(first + step) % size] {
+            if run > gap {
+                gap = run;
+                gap_start = (first + step - run) % size;
+            }
+            run = 0;
+        } else {
+            run += 1;
+        }
+    }
+    if gap == 0 {
+        return Some((first as u32, size as u32));
+    }
+    Some((((gap_start + gap) % size) as u32, (size - gap) as u32))
+}
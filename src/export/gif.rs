@@ -0,0 +1,181 @@
+//! Animated GIF export of a run of generations.
+//!
+//! Every frame is a full render using a global palette of the grid, dead and
+//! live colors, compressed with GIF's variable width LZW. The animation
+//! loops forever.
+
+use std::collections::HashMap;
+
+use super::{render, BitWriter, RenderOptions, Rgba};
+use crate::image::ImageError;
+use crate::universe::{Region, Universe};
+
+pub const SIGNATURE: &[u8] = b"GIF89a";
+
+// Four palette entries, the smallest table GIF allows past two
+const PALETTE_BITS: u8 = 2;
+const MAX_CODES: u16 = 4096;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GifOptions {
+    /// Number of generations to record, starting with the current one.
+    pub frames: u32,
+    /// Time each frame is shown, in hundredths of a second.
+    pub delay: u16,
+    /// Part of the board to record, `None` for all of it.
+    pub crop: Option<Region>,
+    pub render: RenderOptions,
+}
+
+impl Default for GifOptions {
+    fn default() -> GifOptions {
+        GifOptions {
+            frames: 30,
+            delay: 10,
+            crop: None,
+            render: RenderOptions::default(),
+        }
+    }
+}
+
+/// Records `options.frames` generations of `universe`, which is left as it
+/// was. Fails when a frame would be larger than GIF's 65535 pixel limit or
+/// has more pixels than `render` allows.
+pub fn encode(universe: &Universe, options: &GifOptions) -> Result<Vec<u8>, ImageError> {
+    let (width, height) = match options.crop {
+        Some(region) => (region.width, region.height),
+        None => (universe.width(), universe.height()),
+    };
+    let (image_width, image_height) = options.render.image_size(width, height);
+    if image_width > u16::MAX as u32 || image_height > u16::MAX as u32 {
+        return Err(ImageError::TooLarge {
+            width: image_width,
+            height: image_height,
+        });
+    }
+
+    let palette = [
+        options.render.grid_color,
+        options.render.dead_color,
+        options.render.alive_color,
+        Rgba::opaque(0, 0, 0),
+    ];
+
+    let mut out = SIGNATURE.to_vec();
+    out.extend_from_slice(&(image_width as u16).to_le_bytes());
+    out.extend_from_slice(&(image_height as u16).to_le_bytes());
+    // Global color table with eight bit color resolution, background 0 and
+    // square pixels
+    out.extend_from_slice(&[0xF0 | (PALETTE_BITS - 1), 0, 0]);
+    for color in palette.iter() {
+        out.extend_from_slice(&[color.r, color.g, color.b]);
+    }
+
+    // Netscape extension with a loop count of 0, forever
+    out.extend_from_slice(&[0x21, 0xFF, 0x0B]);
+    out.extend_from_slice(b"NETSCAPE2.0");
+    out.extend_from_slice(&[0x03, 0x01, 0x00, 0x00, 0x00]);
+
+    let mut universe = universe.clone();
+    for frame in 0..options.frames {
+        if frame > 0 {
+            universe.tick();
+        }
+        let board = match options.crop {
+            Some(region) => universe.copy_region(region),
+            None => universe.clone(),
+        };
+        let image = render(&board, &options.render)?;
+        let indices: Vec<u8> = image
+            .pixels
+            .chunks(4)
+            .map(|pixel| {
+                let color = Rgba {
+                    r: pixel[0],
+                    g: pixel[1],
+                    b: pixel[2],
+                    a: pixel[3],
+                };
+                palette.iter().position(|&entry| entry == color).unwrap() as u8
+            })
+            .collect();
+
+        // Graphic control extension: leave the frame in place, then delay
+        out.extend_from_slice(&[0x21, 0xF9, 0x04, 0x04]);
+        out.extend_from_slice(&options.delay.to_le_bytes());
+        out.extend_from_slice(&[0x00, 0x00]);
+
+        // Image descriptor covering the whole screen, no local color table
+        out.push(0x2C);
+        out.extend_from_slice(&[0, 0, 0, 0]);
+        out.extend_from_slice(&(image_width as u16).to_le_bytes());
+        out.extend_from_slice(&(image_height as u16).to_le_bytes());
+        out.push(0x00);
+
+        out.push(PALETTE_BITS);
+        for block in lzw(&indices, PALETTE_BITS).chunks(255) {
+            out.push(block.len() as u8);
+            out.extend_from_slice(block);
+        }
+        out.push(0x00);
+    }
+
+    out.push(0x3B);
+    Ok(out)
+}
+
+fn lzw(indices: &[u8], min_code_size: u8) -> Vec<u8> {
+    let clear = 1u16 << min_code_size;
+    let end = clear + 1;
+    let mut table: HashMap<(u16, u8), u16> = HashMap::new();
+    let mut next = end + 1;
+    let mut code_size = min_code_size + 1;
+
+    let mut bits = BitWriter::default();
+    bits.write(clear as u32, code_size);
+
+    let mut prefix: Option<u16> = None;
+    for &index in indices.iter() {
+        let current = match prefix {
+            None => {
+                prefix = Some(index as u16);
+                continue;
+            }
+            Some(current) => current,
+        };
+        if let Some(&code) = table.get(&(current, index)) {
+            prefix = Some(code);
+            continue;
+        }
+
+        bits.write(current as u32, code_size);
+        if next == MAX_CODES {
+            // The table is full, so start over
+            bits.write(clear as u32, code_size);
+            table.clear();
+            next = end + 1;
+            code_size = min_code_size + 1;
+        } else {
+            // Decoders widen codes once the table outgrows the current size
+            if next >= 1 << code_size {
+                code_size += 1;
+            }
+            table.insert((current, index), next);
+            next += 1;
+        }
+        prefix = Some(index as u16);
+    }
+
+    if let Some(current) = prefix {
+        bits.write(current as u32, code_size);
+    }
+    bits.write(end as u32, code_size);
+    bits.finish()
+}
+
+impl Universe {
+    /// Records generations of the universe as an animated GIF.
+    pub fn to_gif(&self, options: &GifOptions) -> Result<Vec<u8>, ImageError> {
+        encode(self, options)
+    }
+}
@@ -0,0 +1,178 @@
+//! Rendering boards to images without a browser.
+//!
+//! `render` lays cells out the way `draw_grid` and `draw_cells` do on the
+//! page, so an exported image matches what the canvas shows.
+
+use crate::cell::Cell;
+use crate::formats::MAX_CELLS;
+use crate::image::ImageError;
+use crate::universe::Universe;
+
+pub mod gif;
+pub mod png;
+pub mod svg;
+
+/// An 8-bit RGBA color.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rgba {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+impl Rgba {
+    pub const fn opaque(r: u8, g: u8, b: u8) -> Rgba {
+        Rgba { r, g, b, a: 255 }
+    }
+
+    /// Parses a `#RRGGBB` or `#RRGGBBAA` color.
+    pub fn from_hex(hex: &str) -> Option<Rgba> {
+        let digits = hex.strip_prefix('#')?;
+        if !(digits.len() == 6 || digits.len() == 8) || !digits.is_ascii() {
+            return None;
+        }
+        let channel = |idx: usize| u8::from_str_radix(&digits[idx * 2..idx * 2 + 2], 16).ok();
+        Some(Rgba {
+            r: channel(0)?,
+            g: channel(1)?,
+            b: channel(2)?,
+            a: if digits.len() == 8 { channel(3)? } else { 255 },
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RenderOptions {
+    /// Side of a cell in pixels, not counting grid lines.
+    pub cell_size: u32,
+    /// Draws a one pixel line around every cell.
+    pub grid: bool,
+    pub grid_color: Rgba,
+    pub alive_color: Rgba,
+    pub dead_color: Rgba,
+}
+
+/// The page's cell size and colors.
+impl Default for RenderOptions {
+    fn default() -> RenderOptions {
+        let color = |hex| Rgba::from_hex(hex).unwrap();
+        RenderOptions {
+            cell_size: crate::CELL_SIZE,
+            grid: true,
+            grid_color: color(crate::GRID_COLOR),
+            alive_color: color(crate::ALIVE_COLOR),
+            dead_color: color(crate::DEAD_COLOR),
+        }
+    }
+}
+
+impl RenderOptions {
+    /// Distance in pixels from one cell to the next.
+    pub fn pitch(&self) -> u32 {
+        self.cell_size.saturating_add(self.grid as u32)
+    }
+
+    /// Pixel size of a `width` x `height` board, saturating at `u32::MAX`.
+    pub fn image_size(&self, width: u32, height: u32) -> (u32, u32) {
+        let border = self.grid as u32;
+        let side = |cells: u32| cells.saturating_mul(self.pitch()).saturating_add(border);
+        (side(width), side(height))
+    }
+}
+
+/// Pixels in row-major order, four bytes each.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RgbaImage {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl RgbaImage {
+    pub fn get(&self, x: u32, y: u32) -> Rgba {
+        let idx = ((y * self.width + x) * 4) as usize;
+        let pixel = &self.pixels[idx..idx + 4];
+        Rgba {
+            r: pixel[0],
+            g: pixel[1],
+            b: pixel[2],
+            a: pixel[3],
+        }
+    }
+}
+
+/// Draws `universe` the way the page does: with a grid, each cell starts one
+/// pixel past its grid line. Fails when the image would have more than
+/// `MAX_CELLS` pixels.
+pub fn render(universe: &Universe, options: &RenderOptions) -> Result<RgbaImage, ImageError> {
+    let (width, height) = options.image_size(universe.width(), universe.height());
+    let pitch = options.pitch();
+    let offset = options.grid as u32;
+
+    let pixel_count = width as u64 * height as u64;
+    if pixel_count > MAX_CELLS {
+        return Err(ImageError::TooLarge { width, height });
+    }
+
+    let mut pixels = Vec::with_capacity(pixel_count as usize * 4);
+    for y in 0..height {
+        for x in 0..width {
+            let on_grid = options.grid && (x % pitch == 0 || y % pitch == 0);
+            let color = if on_grid {
+                options.grid_color
+            } else {
+                let row = (y - offset) / pitch;
+                let col = (x - offset) / pitch;
+                match universe.cells()[universe.get_index(row, col)] {
+                    Cell::Alive => options.alive_color,
+                    Cell::Dead => options.dead_color,
+                }
+            };
+            pixels.extend_from_slice(&[color.r, color.g, color.b, color.a]);
+        }
+    }
+
+    Ok(RgbaImage {
+        width,
+        height,
+        pixels,
+    })
+}
+
+impl Universe {
+    /// Renders the universe and encodes it as a PNG file.
+    pub fn to_png(&self, options: &RenderOptions) -> Result<Vec<u8>, ImageError> {
+        Ok(png::encode(&render(self, options)?))
+    }
+}
+
+// Packs values into bytes least significant bit first, as deflate and GIF's
+// LZW both expect
+#[derive(Default)]
+struct BitWriter {
+    out: Vec<u8>,
+    buffer: u32,
+    count: u8,
+}
+
+impl BitWriter {
+    fn write(&mut self, value: u32, length: u8) {
+        for bit in 0..length {
+            self.buffer |= ((value >> bit) & 1) << self.count;
+            self.count += 1;
+            if self.count == 8 {
+                self.out.push(self.buffer as u8);
+                self.buffer = 0;
+                self.count = 0;
+            }
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.count > 0 {
+            self.out.push(self.buffer as u8);
+        }
+        self.out
+    }
+}
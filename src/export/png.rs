@@ -0,0 +1,195 @@
+//! A small PNG encoder for rendered boards.
+//!
+//! Rows are filtered with whichever of None, Sub or Up leaves the smallest
+//! values, then compressed as a single fixed-Huffman deflate block that only
+//! looks for repeated bytes. Boards are mostly runs of one color, so that is
+//! enough to get files close to what a general purpose encoder produces.
+
+use super::{BitWriter, RgbaImage};
+use crate::checksum::{adler32, crc32};
+
+pub const SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+// Truecolor with alpha, eight bits per channel
+const BIT_DEPTH: u8 = 8;
+const COLOR_TYPE: u8 = 6;
+const BYTES_PER_PIXEL: usize = 4;
+
+const FILTER_NONE: u8 = 0;
+const FILTER_SUB: u8 = 1;
+const FILTER_UP: u8 = 2;
+
+pub fn encode(image: &RgbaImage) -> Vec<u8> {
+    let mut out = SIGNATURE.to_vec();
+
+    let mut header = vec![];
+    header.extend_from_slice(&image.width.to_be_bytes());
+    header.extend_from_slice(&image.height.to_be_bytes());
+    // Bit depth, color type, then default compression, filtering and no
+    // interlacing
+    header.extend_from_slice(&[BIT_DEPTH, COLOR_TYPE, 0, 0, 0]);
+    write_chunk(&mut out, b"IHDR", &header);
+    write_chunk(&mut out, b"IDAT", &zlib(&filter(image)));
+    write_chunk(&mut out, b"IEND", &[]);
+
+    out
+}
+
+fn write_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend_from_slice(kind);
+    out.extend_from_slice(data);
+    let checksum = crc32(&out[start..]);
+    out.extend_from_slice(&checksum.to_be_bytes());
+}
+
+// Each row gets the filter whose output has the smallest sum, read as
+// signed bytes, as the PNG specification suggests
+fn filter(image: &RgbaImage) -> Vec<u8> {
+    let stride = image.width as usize * BYTES_PER_PIXEL;
+    let mut out = Vec::with_capacity((stride + 1) * image.height as usize);
+    let empty = vec![0; stride];
+
+    for (idx, row) in image.pixels.chunks(stride.max(1)).enumerate() {
+        let above = if idx == 0 {
+            &empty[..]
+        } else {
+            &image.pixels[(idx - 1) * stride..idx * stride]
+        };
+        let candidates = [
+            (FILTER_NONE, row.to_vec()),
+            (
+                FILTER_SUB,
+                (0..stride)
+                    .map(|i| {
+                        let left = if i >= BYTES_PER_PIXEL {
+                            row[i - BYTES_PER_PIXEL]
+                        } else {
+                            0
+                        };
+                        row[i].wrapping_sub(left)
+                    })
+                    .collect(),
+            ),
+            (
+                FILTER_UP,
+                (0..stride).map(|i| row[i].wrapping_sub(above[i])).collect(),
+            ),
+        ];
+        let score = |bytes: &Vec<u8>| -> u64 {
+            bytes
+                .iter()
+                .map(|&byte| (byte as i8).unsigned_abs() as u64)
+                .sum()
+        };
+        let (kind, filtered) = candidates
+            .iter()
+            .min_by_key(|(_, bytes)| score(bytes))
+            .unwrap();
+        out.push(*kind);
+        out.extend_from_slice(filtered);
+    }
+
+    out
+}
+
+fn zlib(data: &[u8]) -> Vec<u8> {
+    // 32K window, no preset dictionary, fastest compression
+    let mut out = vec![0x78, 0x01];
+    out.extend_from_slice(&deflate(data));
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
+
+// (base length, extra bits) for length codes 257 to 285
+const LENGTHS: [(u16, u8); 29] = [
+    (3, 0),
+    (4, 0),
+    (5, 0),
+    (6, 0),
+    (7, 0),
+    (8, 0),
+    (9, 0),
+    (10, 0),
+    (11, 1),
+    (13, 1),
+    (15, 1),
+    (17, 1),
+    (19, 2),
+    (23, 2),
+    (27, 2),
+    (31, 2),
+    (35, 3),
+    (43, 3),
+    (51, 3),
+    (59, 3),
+    (67, 4),
+    (83, 4),
+    (99, 4),
+    (115, 4),
+    (131, 5),
+    (163, 5),
+    (195, 5),
+    (227, 5),
+    (258, 0),
+];
+
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+const END_OF_BLOCK: u16 = 256;
+
+// One final fixed-Huffman block, where every run of a repeated byte after
+// its first becomes a match one byte back
+fn deflate(data: &[u8]) -> Vec<u8> {
+    let mut bits = BitWriter::default();
+    // BFINAL, then BTYPE 01 for fixed codes
+    bits.write(1, 1);
+    bits.write(1, 2);
+
+    let mut idx = 0;
+    while idx < data.len() {
+        bits.symbol(data[idx] as u16);
+        let run = data[idx + 1..]
+            .iter()
+            .take(MAX_MATCH)
+            .take_while(|&&byte| byte == data[idx])
+            .count();
+        idx += 1;
+        if run < MIN_MATCH {
+            continue;
+        }
+
+        let code = LENGTHS
+            .iter()
+            .rposition(|&(base, _)| base as usize <= run)
+            .unwrap();
+        let (base, extra) = LENGTHS[code];
+        bits.symbol(257 + code as u16);
+        bits.write((run - base as usize) as u32, extra);
+        // Distance code 0, a distance of one byte, as five bits
+        bits.huffman(0, 5);
+        idx += run;
+    }
+
+    bits.symbol(END_OF_BLOCK);
+    bits.finish()
+}
+
+impl BitWriter {
+    // Huffman codes are packed most significant bit first
+    fn huffman(&mut self, code: u16, length: u8) {
+        let reversed = (code as u32).reverse_bits() >> (32 - length as u32);
+        self.write(reversed, length);
+    }
+
+    // Writes a literal/length symbol with the fixed code table
+    fn symbol(&mut self, symbol: u16) {
+        match symbol {
+            0..=143 => self.huffman(0x30 + symbol, 8),
+            144..=255 => self.huffman(0x190 + symbol - 144, 9),
+            256..=279 => self.huffman(symbol - 256, 7),
+            _ => self.huffman(0xC0 + symbol - 280, 8),
+        }
+    }
+}
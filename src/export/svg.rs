@@ -0,0 +1,148 @@
+//! SVG export, for thumbnails and printing.
+//!
+//! Live cells are merged into as few rectangles as possible: each row is cut
+//! into runs, and a run that continues straight down grows taller instead
+//! of starting a new rectangle. The grid is drawn with dashed lines, like
+//! the hand-made previews the palette used to have.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use super::{RenderOptions, Rgba};
+use crate::cell::Cell;
+use crate::universe::Universe;
+
+pub fn encode(universe: &Universe, options: &RenderOptions) -> String {
+    let (width, height) = options.image_size(universe.width(), universe.height());
+    let pitch = options.pitch();
+    let offset = options.grid as u32;
+
+    let mut out = String::new();
+    writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+        w = width,
+        h = height
+    )
+    .unwrap();
+    writeln!(
+        out,
+        r#"<rect width="100%" height="100%"{}/>"#,
+        fill(options.dead_color)
+    )
+    .unwrap();
+
+    // (row, column, height, width) of each merged rectangle, in cells
+    let mut rects = vec![];
+    // Rectangles still growing, keyed by their (column, width)
+    let mut open: HashMap<(u32, u32), (u32, u32)> = HashMap::new();
+    for row in 0..=universe.height() {
+        let runs = if row < universe.height() {
+            runs(universe, row)
+        } else {
+            vec![]
+        };
+        let mut still_open = HashMap::new();
+        for run in runs {
+            let (top, rows) = open.remove(&run).unwrap_or((row, 0));
+            still_open.insert(run, (top, rows + 1));
+        }
+        rects.extend(
+            open.drain()
+                .map(|((col, cols), (top, rows))| (top, col, rows, cols)),
+        );
+        open = still_open;
+    }
+    rects.sort_unstable();
+
+    if !rects.is_empty() {
+        writeln!(out, "<g{}>", fill(options.alive_color)).unwrap();
+        for (row, col, rows, cols) in rects {
+            writeln!(
+                out,
+                r#"<rect x="{}" y="{}" width="{}" height="{}"/>"#,
+                col * pitch + offset,
+                row * pitch + offset,
+                cols * pitch - offset,
+                rows * pitch - offset
+            )
+            .unwrap();
+        }
+        out.push_str("</g>\n");
+    }
+
+    if options.grid {
+        // In the style of the old hand-made previews: a line as wide as the
+        // image, dashed one pixel on and a cell off, draws every grid line
+        // across it
+        let stroke = format!(
+            r#" stroke="{}"{} stroke-dasharray="1,{}""#,
+            hex(options.grid_color),
+            opacity("stroke-opacity", options.grid_color),
+            options.cell_size
+        );
+        writeln!(
+            out,
+            r#"<line x1="{x}" y1="0" x2="{x}" y2="{h}" stroke-width="{w}"{s}/>"#,
+            x = width as f64 / 2.0,
+            w = width,
+            h = height,
+            s = stroke
+        )
+        .unwrap();
+        writeln!(
+            out,
+            r#"<line x1="0" y1="{y}" x2="{w}" y2="{y}" stroke-width="{h}"{s}/>"#,
+            y = height as f64 / 2.0,
+            w = width,
+            h = height,
+            s = stroke
+        )
+        .unwrap();
+    }
+
+    out.push_str("</svg>\n");
+    out
+}
+
+// (column, width) of each run of live cells in `row`
+fn runs(universe: &Universe, row: u32) -> Vec<(u32, u32)> {
+    let mut runs: Vec<(u32, u32)> = vec![];
+    for col in 0..universe.width() {
+        if universe.cells()[universe.get_index(row, col)] != Cell::Alive {
+            continue;
+        }
+        match runs.last_mut() {
+            Some((start, width)) if *start + *width == col => *width += 1,
+            _ => runs.push((col, 1)),
+        }
+    }
+    runs
+}
+
+fn hex(color: Rgba) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.r, color.g, color.b)
+}
+
+fn opacity(attribute: &str, color: Rgba) -> String {
+    if color.a == 255 {
+        String::new()
+    } else {
+        format!(r#" {}="{:.3}""#, attribute, color.a as f64 / 255.0)
+    }
+}
+
+fn fill(color: Rgba) -> String {
+    format!(
+        r#" fill="{}"{}"#,
+        hex(color),
+        opacity("fill-opacity", color)
+    )
+}
+
+impl Universe {
+    /// Draws the universe as an SVG document, in the page's layout.
+    pub fn to_svg(&self, options: &RenderOptions) -> String {
+        encode(self, options)
+    }
+}
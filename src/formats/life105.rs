@@ -0,0 +1,163 @@
+//! Life 1.05 patterns: a `#Life 1.05` header, `#D` descriptions, a `#N` or
+//! `#R` rule line and `#P x y` blocks of `.` and `*` rows.
+//!
+//! ```text
+//! #Life 1.05
+//! #D Glider
+//! #N
+//! #P -1 -1
+//! .*
+//! ..*
+//! ***
+//! ```
+
+use std::fmt::Write;
+
+use super::{from_coordinates, ParseError, Pattern};
+use crate::cell::Cell;
+
+pub const HEADER: &str = "#Life 1.05";
+
+pub fn parse(text: &str) -> Result<Pattern, ParseError> {
+    let mut coordinates = vec![];
+    let mut comments = vec![];
+    let mut rule = None;
+    // Top left corner of the current `#P` block and the row within it
+    let mut block: Option<(i64, i64, i64)> = None;
+    let mut last_line = 1;
+
+    for (line_idx, line) in text.lines().enumerate() {
+        let line_no = line_idx + 1;
+        let line = line.trim_end();
+        if line_no == 1 {
+            if line.trim() != HEADER {
+                return Err(ParseError::new(1, 1, "expected `#Life 1.05` header"));
+            }
+            continue;
+        }
+
+        if let Some(description) = line.strip_prefix("#D") {
+            comments.push(description.trim().to_string());
+        } else if line.starts_with("#N") {
+            rule = Some(String::from("B3/S23"));
+        } else if let Some(survival_birth) = line.strip_prefix("#R") {
+            rule = Some(parse_rule(survival_birth.trim()).ok_or_else(|| {
+                ParseError::new(
+                    line_no,
+                    4,
+                    format!("invalid rule '{}'", survival_birth.trim()),
+                )
+            })?);
+        } else if let Some(position) = line.strip_prefix("#P") {
+            let mut fields = position
+                .split_whitespace()
+                .map(|field| field.parse::<i64>());
+            match (fields.next(), fields.next(), fields.next()) {
+                (Some(Ok(x)), Some(Ok(y)), None) => block = Some((x, y, 0)),
+                _ => {
+                    return Err(ParseError::new(
+                        line_no,
+                        3,
+                        "expected `#P x y` block position",
+                    ));
+                }
+            }
+        } else if line.starts_with('#') || line.is_empty() {
+            continue;
+        } else {
+            let (x, y, row) = match block.as_mut() {
+                Some(block) => block,
+                None => {
+                    return Err(ParseError::new(
+                        line_no,
+                        1,
+                        "cell rows need a `#P` line first",
+                    ));
+                }
+            };
+            for (col_idx, ch) in line.chars().enumerate() {
+                match ch {
+                    '.' => {}
+                    '*' => {
+                        let cell = x.checked_add(col_idx as i64).zip(y.checked_add(*row));
+                        coordinates.push(cell.ok_or_else(|| {
+                            ParseError::new(line_no, col_idx + 1, "cell position is out of range")
+                        })?);
+                    }
+                    ch => {
+                        return Err(ParseError::new(
+                            line_no,
+                            col_idx + 1,
+                            format!("unexpected character '{}'", ch),
+                        ));
+                    }
+                }
+            }
+            *row += 1;
+            last_line = line_no;
+        }
+    }
+
+    let mut pattern = from_coordinates(&coordinates, last_line)?;
+    pattern.rule = rule;
+    pattern.comments = comments;
+    Ok(pattern)
+}
+
+// Converts survival/birth notation like `23/3` to `B3/S23`
+fn parse_rule(survival_birth: &str) -> Option<String> {
+    let (survival, birth) = survival_birth.split_once('/')?;
+    let all_digits = |part: &str| part.chars().all(|ch| ch.is_ascii_digit());
+    if !all_digits(survival) || !all_digits(birth) {
+        return None;
+    }
+    Some(format!("B{}/S{}", birth, survival))
+}
+
+// Converts `B3/S23` back to survival/birth notation, if it has that shape
+fn format_rule(rule: &str) -> Option<String> {
+    let (birth, survival) = rule.split_once('/')?;
+    let (birth, survival) = (birth.strip_prefix('B')?, survival.strip_prefix('S')?);
+    Some(format!("{}/{}", survival, birth))
+}
+
+pub fn write(pattern: &Pattern) -> String {
+    let universe = &pattern.universe;
+    let mut out = String::new();
+
+    writeln!(out, "{}", HEADER).unwrap();
+    if let Some(name) = pattern.name.as_ref() {
+        writeln!(out, "#D {}", name).unwrap();
+    }
+    for comment in pattern.comments.iter() {
+        writeln!(out, "#D {}", comment).unwrap();
+    }
+    match pattern.rule.as_deref() {
+        None | Some("B3/S23") => writeln!(out, "#N").unwrap(),
+        Some(rule) => match format_rule(rule) {
+            Some(survival_birth) => writeln!(out, "#R {}", survival_birth).unwrap(),
+            None => writeln!(out, "#N").unwrap(),
+        },
+    }
+
+    // Life 1.05 blocks are conventionally centered on the origin
+    writeln!(
+        out,
+        "#P {} {}",
+        -(universe.width() as i64 / 2),
+        -(universe.height() as i64 / 2)
+    )
+    .unwrap();
+    for row in 0..universe.height() {
+        let line: String = (0..universe.width())
+            .map(|col| match universe.cells()[universe.get_index(row, col)] {
+                Cell::Alive => '*',
+                Cell::Dead => '.',
+            })
+            .collect();
+        let line = line.trim_end_matches('.');
+        writeln!(out, "{}", if line.is_empty() { "." } else { line }).unwrap();
+    }
+
+    out
+}
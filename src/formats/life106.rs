@@ -0,0 +1,74 @@
+//! Life 1.06 patterns: a `#Life 1.06` header followed by one `x y`
+//! coordinate pair per live cell.
+
+use std::fmt::Write;
+
+use super::{from_coordinates, ParseError, Pattern};
+use crate::cell::Cell;
+
+pub const HEADER: &str = "#Life 1.06";
+
+pub fn parse(text: &str) -> Result<Pattern, ParseError> {
+    let mut coordinates = vec![];
+    let mut last_line = 1;
+
+    for (line_idx, line) in text.lines().enumerate() {
+        let line_no = line_idx + 1;
+        let trimmed = line.trim();
+        if line_no == 1 {
+            if trimmed != HEADER {
+                return Err(ParseError::new(1, 1, "expected `#Life 1.06` header"));
+            }
+            continue;
+        }
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+
+        let mut offset = 0;
+        let mut pair = [0i64; 2];
+        let mut fields = 0;
+        for field in line.split_whitespace() {
+            let column = offset + line[offset..].find(field).unwrap() + 1;
+            offset = column - 1 + field.len();
+            if fields == 2 {
+                return Err(ParseError::new(
+                    line_no,
+                    column,
+                    "expected only an `x y` coordinate pair",
+                ));
+            }
+            pair[fields] = field.parse().map_err(|_| {
+                ParseError::new(line_no, column, format!("invalid coordinate '{}'", field))
+            })?;
+            fields += 1;
+        }
+        if fields < 2 {
+            return Err(ParseError::new(
+                line_no,
+                offset + 1,
+                "expected an `x y` coordinate pair",
+            ));
+        }
+        coordinates.push((pair[0], pair[1]));
+        last_line = line_no;
+    }
+
+    from_coordinates(&coordinates, last_line)
+}
+
+pub fn write(pattern: &Pattern) -> String {
+    let universe = &pattern.universe;
+    let mut out = String::new();
+
+    writeln!(out, "{}", HEADER).unwrap();
+    for row in 0..universe.height() {
+        for col in 0..universe.width() {
+            if universe.cells()[universe.get_index(row, col)] == Cell::Alive {
+                writeln!(out, "{} {}", col, row).unwrap();
+            }
+        }
+    }
+
+    out
+}
@@ -0,0 +1,267 @@
+//! Golly macrocell (`.mc`) patterns, which store a deduplicated quadtree so
+//! huge patterns stay small on disk.
+//!
+//! ```text
+//! [M2] (golly 4.0)
+//! #R B3/S23
+//! .*$..*$***$
+//! 4 1 0 0 0
+//! ```
+//!
+//! After the header, each line defines the next node, numbered from 1. A line
+//! of `.`, `*` and `$` is an 8x8 leaf with rows ended by `$`; a line of
+//! `level nw ne sw se` is a branch whose quadrants refer to earlier nodes, 0
+//! meaning empty. The last node is the root.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use super::ParseError;
+use crate::quadtree::{Node, NodeId, QuadTree, EMPTY, LEAF_LEVEL, MAX_LEVEL};
+
+pub const HEADER: &str = "[M2]";
+
+/// A macrocell file, kept as a quadtree instead of a dense universe.
+#[derive(Clone, Debug)]
+pub struct MacrocellPattern {
+    pub tree: QuadTree,
+    pub rule: Option<String>,
+    pub comments: Vec<String>,
+}
+
+pub fn parse(text: &str) -> Result<MacrocellPattern, ParseError> {
+    let mut tree = QuadTree::new();
+    let mut rule = None;
+    let mut comments = vec![];
+    // Node numbers from the file mapped to tree ids and levels, 0 unused
+    let mut nodes: Vec<(NodeId, u32)> = vec![(EMPTY, 0)];
+
+    for (line_idx, line) in text.lines().enumerate() {
+        let line_no = line_idx + 1;
+        let line = line.trim_end();
+        if line_no == 1 {
+            if !line.starts_with(HEADER) {
+                return Err(ParseError::new(1, 1, "expected `[M2]` header"));
+            }
+            continue;
+        }
+
+        if let Some(rule_text) = line.strip_prefix("#R") {
+            rule = Some(rule_text.trim().to_string());
+        } else if let Some(comment) = line.strip_prefix("#C") {
+            comments.push(comment.trim().to_string());
+        } else if line.starts_with('#') || line.is_empty() {
+            continue;
+        } else if line.starts_with(['.', '*', '$']) {
+            let bits = parse_leaf(line, line_no)?;
+            nodes.push((tree.leaf(bits), LEAF_LEVEL));
+        } else {
+            let node = parse_branch(&mut tree, &nodes, line, line_no)?;
+            nodes.push(node);
+        }
+    }
+
+    if let Some(&(root, level)) = nodes.last() {
+        if nodes.len() > 1 {
+            tree.set_root(root, level);
+        }
+    }
+
+    Ok(MacrocellPattern {
+        tree,
+        rule,
+        comments,
+    })
+}
+
+fn parse_leaf(line: &str, line_no: usize) -> Result<u64, ParseError> {
+    let (mut row, mut col) = (0u64, 0u64);
+    let mut bits = 0;
+
+    for (col_idx, ch) in line.chars().enumerate() {
+        let column = col_idx + 1;
+        match ch {
+            '$' => {
+                row += 1;
+                col = 0;
+                continue;
+            }
+            '.' | '*' => {}
+            ch => {
+                return Err(ParseError::new(
+                    line_no,
+                    column,
+                    format!("unexpected character '{}' in leaf", ch),
+                ));
+            }
+        }
+        if row >= 8 || col >= 8 {
+            return Err(ParseError::new(line_no, column, "leaf is larger than 8x8"));
+        }
+        if ch == '*' {
+            bits |= 1 << (row * 8 + col);
+        }
+        col += 1;
+    }
+
+    Ok(bits)
+}
+
+fn parse_branch(
+    tree: &mut QuadTree,
+    nodes: &[(NodeId, u32)],
+    line: &str,
+    line_no: usize,
+) -> Result<(NodeId, u32), ParseError> {
+    let mut fields = [0usize; 5];
+    let mut count = 0;
+    let mut offset = 0;
+
+    for field in line.split_whitespace() {
+        let column = offset + line[offset..].find(field).unwrap() + 1;
+        offset = column - 1 + field.len();
+        if count == 5 {
+            return Err(ParseError::new(
+                line_no,
+                column,
+                "expected `level nw ne sw se`",
+            ));
+        }
+        fields[count] = field
+            .parse()
+            .map_err(|_| ParseError::new(line_no, column, format!("invalid number '{}'", field)))?;
+
+        if count == 0 && (fields[0] as u32 <= LEAF_LEVEL || fields[0] as u32 > MAX_LEVEL) {
+            return Err(ParseError::new(
+                line_no,
+                column,
+                format!("unsupported node level {}", fields[0]),
+            ));
+        }
+        if count > 0 {
+            let child = fields[count];
+            if child >= nodes.len() {
+                return Err(ParseError::new(
+                    line_no,
+                    column,
+                    format!("node {} is not defined yet", child),
+                ));
+            }
+            let (_, child_level) = nodes[child];
+            if child != 0 && child_level + 1 != fields[0] as u32 {
+                return Err(ParseError::new(
+                    line_no,
+                    column,
+                    format!(
+                        "node {} has level {}, expected {}",
+                        child,
+                        child_level,
+                        fields[0] - 1
+                    ),
+                ));
+            }
+        }
+        count += 1;
+    }
+
+    if count < 5 {
+        return Err(ParseError::new(
+            line_no,
+            offset + 1,
+            "expected `level nw ne sw se`",
+        ));
+    }
+
+    let level = fields[0] as u32;
+    let quadrants = [
+        nodes[fields[1]].0,
+        nodes[fields[2]].0,
+        nodes[fields[3]].0,
+        nodes[fields[4]].0,
+    ];
+    Ok((tree.branch(level, quadrants), level))
+}
+
+pub fn write(pattern: &MacrocellPattern) -> String {
+    let mut out = String::new();
+    writeln!(out, "{} (wasm-game-of-life)", HEADER).unwrap();
+    writeln!(
+        out,
+        "#R {}",
+        pattern.rule.as_deref().unwrap_or(super::rle::DEFAULT_RULE)
+    )
+    .unwrap();
+    for comment in pattern.comments.iter() {
+        writeln!(out, "#C {}", comment).unwrap();
+    }
+
+    let tree = &pattern.tree;
+    if tree.root() == EMPTY {
+        // An empty leaf still gives the file a root
+        out.push_str("$\n");
+        return out;
+    }
+
+    let mut numbers = HashMap::new();
+    write_node(tree, tree.root(), &mut numbers, &mut out);
+    out
+}
+
+// Writes the children of `id` before it, returning its node number
+fn write_node(
+    tree: &QuadTree,
+    id: NodeId,
+    numbers: &mut HashMap<NodeId, usize>,
+    out: &mut String,
+) -> usize {
+    if id == EMPTY {
+        return 0;
+    }
+    if let Some(&number) = numbers.get(&id) {
+        return number;
+    }
+
+    match tree.node(id) {
+        Node::Leaf(bits) => {
+            let rows: Vec<String> = (0..8)
+                .map(|row| {
+                    let line: String = (0..8)
+                        .map(|col| {
+                            if bits & (1 << (row * 8 + col)) != 0 {
+                                '*'
+                            } else {
+                                '.'
+                            }
+                        })
+                        .collect();
+                    line.trim_end_matches('.').to_string()
+                })
+                .collect();
+            let last = rows.iter().rposition(|row| !row.is_empty()).unwrap_or(0);
+            for row in rows[..=last].iter() {
+                out.push_str(row);
+                out.push('$');
+            }
+            out.push('\n');
+        }
+        Node::Branch {
+            level,
+            nw,
+            ne,
+            sw,
+            se,
+        } => {
+            let children = [nw, ne, sw, se].map(|child| write_node(tree, child, numbers, out));
+            writeln!(
+                out,
+                "{} {} {} {} {}",
+                level, children[0], children[1], children[2], children[3]
+            )
+            .unwrap();
+        }
+    }
+
+    let number = numbers.len() + 1;
+    numbers.insert(id, number);
+    number
+}
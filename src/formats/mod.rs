@@ -0,0 +1,323 @@
+//! Readers and writers for the pattern file formats used by other Life
+//! programs.
+
+use std::error::Error;
+use std::fmt;
+
+use crate::quadtree::QuadTree;
+use crate::universe::Universe;
+
+pub mod life105;
+pub mod life106;
+pub mod macrocell;
+pub mod plaintext;
+pub mod rle;
+
+/// Largest board, in cells, a pattern file may expand to.
+pub const MAX_CELLS: u64 = 1 << 26;
+
+/// A pattern read from a file, along with whatever metadata the file carried.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Pattern {
+    pub universe: Universe,
+    pub name: Option<String>,
+    /// Rule string as written in the file, e.g. `B3/S23`.
+    pub rule: Option<String>,
+    pub comments: Vec<String>,
+}
+
+impl From<Universe> for Pattern {
+    fn from(universe: Universe) -> Pattern {
+        Pattern {
+            universe,
+            name: None,
+            rule: None,
+            comments: vec![],
+        }
+    }
+}
+
+/// A pattern file format that can be recognized, read and written.
+pub trait PatternFormat {
+    fn name(&self) -> &'static str;
+
+    /// File extensions, without the dot, this format is usually saved with.
+    fn extensions(&self) -> &'static [&'static str];
+
+    /// Whether `text` looks like this format. Only inspects the start of the
+    /// text, so a match can still fail to parse.
+    fn detect(&self, text: &str) -> bool;
+
+    fn parse(&self, text: &str) -> Result<Pattern, ParseError>;
+
+    fn write(&self, pattern: &Pattern) -> String;
+}
+
+pub struct Rle;
+pub struct Plaintext;
+pub struct Life105;
+pub struct Life106;
+pub struct Macrocell;
+
+/// Every supported format, in the order `detect` tries them.
+pub const FORMATS: &[&dyn PatternFormat] = &[&Macrocell, &Life106, &Life105, &Plaintext, &Rle];
+
+/// Finds the format `text` is written in.
+pub fn detect(text: &str) -> Option<&'static dyn PatternFormat> {
+    FORMATS.iter().cloned().find(|format| format.detect(text))
+}
+
+/// Finds the format usually saved with the file extension `extension`.
+pub fn by_extension(extension: &str) -> Option<&'static dyn PatternFormat> {
+    let extension = extension.trim_start_matches('.').to_ascii_lowercase();
+    FORMATS
+        .iter()
+        .cloned()
+        .find(|format| format.extensions().contains(&extension.as_str()))
+}
+
+/// Parses `text` in whichever supported format it is written in.
+pub fn parse_any(text: &str) -> Result<Pattern, ParseError> {
+    match detect(text) {
+        Some(format) => format.parse(text),
+        None => Err(ParseError::new(1, 1, "unrecognized pattern format")),
+    }
+}
+
+fn first_line(text: &str) -> &str {
+    text.lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .unwrap_or("")
+}
+
+impl PatternFormat for Rle {
+    fn name(&self) -> &'static str {
+        "RLE"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["rle"]
+    }
+
+    fn detect(&self, text: &str) -> bool {
+        text.lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty() && !line.starts_with('#'))
+            .is_some_and(|line| {
+                line.starts_with('x')
+                    || line
+                        .chars()
+                        .all(|ch| ch.is_ascii_digit() || "bo$!".contains(ch))
+            })
+    }
+
+    fn parse(&self, text: &str) -> Result<Pattern, ParseError> {
+        rle::parse(text)
+    }
+
+    fn write(&self, pattern: &Pattern) -> String {
+        rle::write(pattern)
+    }
+}
+
+impl PatternFormat for Plaintext {
+    fn name(&self) -> &'static str {
+        "Plaintext"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["cells", "txt"]
+    }
+
+    fn detect(&self, text: &str) -> bool {
+        let line = first_line(text);
+        line.starts_with('!') || (!line.is_empty() && line.chars().all(|ch| ".O*".contains(ch)))
+    }
+
+    fn parse(&self, text: &str) -> Result<Pattern, ParseError> {
+        plaintext::parse(text)
+    }
+
+    fn write(&self, pattern: &Pattern) -> String {
+        plaintext::write(pattern)
+    }
+}
+
+impl PatternFormat for Life105 {
+    fn name(&self) -> &'static str {
+        "Life 1.05"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["lif", "life"]
+    }
+
+    fn detect(&self, text: &str) -> bool {
+        text.lines().next().map(str::trim) == Some(life105::HEADER)
+    }
+
+    fn parse(&self, text: &str) -> Result<Pattern, ParseError> {
+        life105::parse(text)
+    }
+
+    fn write(&self, pattern: &Pattern) -> String {
+        life105::write(pattern)
+    }
+}
+
+impl PatternFormat for Life106 {
+    fn name(&self) -> &'static str {
+        "Life 1.06"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["lif", "life"]
+    }
+
+    fn detect(&self, text: &str) -> bool {
+        text.lines().next().map(str::trim) == Some(life106::HEADER)
+    }
+
+    fn parse(&self, text: &str) -> Result<Pattern, ParseError> {
+        life106::parse(text)
+    }
+
+    fn write(&self, pattern: &Pattern) -> String {
+        life106::write(pattern)
+    }
+}
+
+/// Reading a macrocell file through this interface expands its live bounding
+/// box into a dense universe, which fails for patterns over `MAX_CELLS`. Use
+/// `macrocell::parse` to keep huge patterns as a quadtree.
+impl PatternFormat for Macrocell {
+    fn name(&self) -> &'static str {
+        "Macrocell"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["mc"]
+    }
+
+    fn detect(&self, text: &str) -> bool {
+        text.starts_with(macrocell::HEADER)
+    }
+
+    fn parse(&self, text: &str) -> Result<Pattern, ParseError> {
+        let macrocell = macrocell::parse(text)?;
+        let universe = match macrocell.tree.bounding_box() {
+            Some((row, column, height, width)) => {
+                if height.saturating_mul(width) > MAX_CELLS {
+                    return Err(ParseError::new(
+                        1,
+                        1,
+                        format!("pattern of {}x{} cells is too large", width, height),
+                    ));
+                }
+                macrocell
+                    .tree
+                    .window(row, column, height as u32, width as u32)
+            }
+            None => Universe::empty(0, 0),
+        };
+
+        Ok(Pattern {
+            universe,
+            name: None,
+            rule: macrocell.rule,
+            comments: macrocell.comments,
+        })
+    }
+
+    fn write(&self, pattern: &Pattern) -> String {
+        macrocell::write(&macrocell::MacrocellPattern {
+            tree: QuadTree::from_universe(&pattern.universe),
+            rule: pattern.rule.clone(),
+            comments: pattern.comments.clone(),
+        })
+    }
+}
+
+/// Malformed pattern input. Lines and columns count from 1.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl ParseError {
+    pub fn new<S: Into<String>>(line: usize, column: usize, message: S) -> ParseError {
+        ParseError {
+            line,
+            column,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+impl Error for ParseError {}
+
+/// Builds a universe from (row, column) live cells, checking the size against
+/// `MAX_CELLS`. `line` is where to report an oversized pattern.
+pub(crate) fn build_universe(
+    width: u64,
+    height: u64,
+    alive: &[(u32, u32)],
+    line: usize,
+) -> Result<Universe, ParseError> {
+    check_size(width as i128, height as i128, line)?;
+
+    let mut universe = Universe::empty(width as u32, height as u32);
+    universe.set_cells(alive);
+    Ok(universe)
+}
+
+/// Builds a pattern from live (x, y) coordinates, shifted so the top left
+/// live cell is at the origin.
+pub(crate) fn from_coordinates(
+    coordinates: &[(i64, i64)],
+    line: usize,
+) -> Result<Pattern, ParseError> {
+    let min_x = coordinates.iter().map(|&(x, _)| x).min().unwrap_or(0);
+    let min_y = coordinates.iter().map(|&(_, y)| y).min().unwrap_or(0);
+    let max_x = coordinates.iter().map(|&(x, _)| x).max().unwrap_or(-1);
+    let max_y = coordinates.iter().map(|&(_, y)| y).max().unwrap_or(-1);
+
+    // Checked in i128 before shifting, so far apart cells can't overflow
+    let width = max_x as i128 - min_x as i128 + 1;
+    let height = max_y as i128 - min_y as i128 + 1;
+    check_size(width, height, line)?;
+
+    let alive: Vec<(u32, u32)> = coordinates
+        .iter()
+        .map(|&(x, y)| ((y - min_y) as u32, (x - min_x) as u32))
+        .collect();
+    build_universe(width as u64, height as u64, &alive, line).map(Pattern::from)
+}
+
+fn check_size(width: i128, height: i128, line: usize) -> Result<(), ParseError> {
+    if width
+        .checked_mul(height)
+        .is_none_or(|cells| cells > MAX_CELLS as i128)
+    {
+        return Err(ParseError::new(
+            line,
+            1,
+            format!("pattern of {}x{} cells is too large", width, height),
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,91 @@
+//! Plaintext (`.cells`) patterns: `!` comment lines followed by rows of `.`
+//! for dead and `O` for live cells.
+//!
+//! ```text
+//! !Name: Glider
+//! .O.
+//! ..O
+//! OOO
+//! ```
+
+use std::fmt::Write;
+
+use super::{build_universe, ParseError, Pattern};
+use crate::cell::Cell;
+
+pub fn parse(text: &str) -> Result<Pattern, ParseError> {
+    let mut name = None;
+    let mut comments = vec![];
+    let mut alive = vec![];
+    let mut rows = 0u64;
+    let (mut width, mut height) = (0u64, 0u64);
+    let mut last_line = 1;
+
+    for (line_idx, line) in text.lines().enumerate() {
+        let line_no = line_idx + 1;
+        let line = line.trim_end();
+
+        if let Some(comment) = line.strip_prefix('!') {
+            match comment.strip_prefix("Name:") {
+                Some(pattern_name) => name = Some(pattern_name.trim().to_string()),
+                None => comments.push(comment.trim().to_string()),
+            }
+            continue;
+        }
+        // Blank lines before the first row are padding, later ones are empty rows
+        if line.is_empty() && rows == 0 {
+            continue;
+        }
+
+        for (col_idx, ch) in line.chars().enumerate() {
+            match ch {
+                '.' => {}
+                'O' | '*' => alive.push((rows as u32, col_idx as u32)),
+                ch => {
+                    return Err(ParseError::new(
+                        line_no,
+                        col_idx + 1,
+                        format!("unexpected character '{}'", ch),
+                    ));
+                }
+            }
+        }
+        rows += 1;
+        // Trailing blank lines are not rows
+        if !line.is_empty() {
+            width = width.max(line.chars().count() as u64);
+            height = rows;
+            last_line = line_no;
+        }
+    }
+
+    let universe = build_universe(width, height, &alive, last_line)?;
+
+    Ok(Pattern {
+        universe,
+        name,
+        rule: None,
+        comments,
+    })
+}
+
+pub fn write(pattern: &Pattern) -> String {
+    let universe = &pattern.universe;
+    let mut out = String::new();
+
+    if let Some(name) = pattern.name.as_ref() {
+        writeln!(out, "!Name: {}", name).unwrap();
+    }
+    for comment in pattern.comments.iter() {
+        writeln!(out, "!{}", comment).unwrap();
+    }
+    for row in 0..universe.height() {
+        for col in 0..universe.width() {
+            let cell = universe.cells()[universe.get_index(row, col)];
+            out.push(if cell == Cell::Alive { 'O' } else { '.' });
+        }
+        out.push('\n');
+    }
+
+    out
+}
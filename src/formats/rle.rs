@@ -0,0 +1,303 @@
+//! Run Length Encoded (`.rle`) patterns, the format used by Golly and
+//! LifeWiki.
+//!
+//! ```text
+//! #N Glider
+//! x = 3, y = 3, rule = B3/S23
+//! bo$2bo$3o!
+//! ```
+
+use std::fmt::Write;
+
+use super::{build_universe, ParseError, Pattern, MAX_CELLS};
+use crate::cell::Cell;
+use crate::universe::Universe;
+
+pub const DEFAULT_RULE: &str = "B3/S23";
+
+// Golly and LifeWiki keep RLE data lines under 70 characters
+const LINE_LENGTH: usize = 70;
+
+pub fn parse(text: &str) -> Result<Pattern, ParseError> {
+    let mut name = None;
+    let mut rule = None;
+    let mut comments = vec![];
+    let mut size: Option<(u64, u64)> = None;
+
+    let mut alive = vec![];
+    let (mut row, mut col) = (0u64, 0u64);
+    let (mut width, mut height) = (0u64, 0u64);
+    // Pending run count, with where it started for error reporting
+    let mut count: Option<(u64, usize, usize)> = None;
+    let mut seen_data = false;
+    let mut last_line = 0;
+
+    'lines: for (line_idx, line) in text.lines().enumerate() {
+        let line_no = line_idx + 1;
+        last_line = line_no;
+        let trimmed = line.trim();
+
+        if trimmed.starts_with('#') {
+            let mut tag = trimmed.chars().skip(1);
+            let text = trimmed.get(2..).unwrap_or("").trim().to_string();
+            match tag.next() {
+                Some('N') => name = Some(text),
+                Some('C') | Some('c') | Some('O') => comments.push(text),
+                _ => {}
+            }
+            continue;
+        }
+        if trimmed.is_empty() {
+            continue;
+        }
+        if !seen_data && size.is_none() && trimmed.starts_with('x') {
+            let ((x, y), header_rule) = parse_header(line, line_no)?;
+            // Checked before any cells are decoded, so a huge header can't
+            // make the data lines allocate
+            if x * y > MAX_CELLS {
+                return Err(ParseError::new(
+                    line_no,
+                    1,
+                    format!("pattern of {}x{} cells is too large", x, y),
+                ));
+            }
+            size = Some((x, y));
+            rule = header_rule;
+            continue;
+        }
+
+        seen_data = true;
+        for (col_idx, ch) in line.chars().enumerate() {
+            let column = col_idx + 1;
+            match ch {
+                '0'..='9' => {
+                    let digit = ch.to_digit(10).unwrap() as u64;
+                    let (value, start_line, start_column) = count.unwrap_or((0, line_no, column));
+                    let value = value * 10 + digit;
+                    if value > MAX_CELLS {
+                        return Err(ParseError::new(
+                            start_line,
+                            start_column,
+                            "run count is too large",
+                        ));
+                    }
+                    count = Some((value, start_line, start_column));
+                }
+                'b' | 'o' | '$' => {
+                    let run = count.take().map(|(value, _, _)| value).unwrap_or(1);
+                    if ch == '$' {
+                        row += run;
+                        col = 0;
+                        continue;
+                    }
+
+                    let end = col + run;
+                    let out_of_bounds = match size {
+                        Some((header_width, header_height)) => {
+                            end > header_width || row >= header_height
+                        }
+                        None => end
+                            .checked_mul(row + 1)
+                            .is_none_or(|cells| cells > MAX_CELLS),
+                    };
+                    if out_of_bounds && ch == 'o' {
+                        return Err(ParseError::new(
+                            line_no,
+                            column,
+                            "live cells extend past the pattern size",
+                        ));
+                    }
+                    if ch == 'o' {
+                        alive.extend((col..end).map(|c| (row as u32, c as u32)));
+                        width = width.max(end);
+                        height = height.max(row + 1);
+                    }
+                    col = end;
+                }
+                '!' => break 'lines,
+                ch if ch.is_whitespace() => {}
+                ch => {
+                    return Err(ParseError::new(
+                        line_no,
+                        column,
+                        format!("unexpected character '{}'", ch),
+                    ));
+                }
+            }
+        }
+    }
+
+    if let Some((_, line, column)) = count {
+        return Err(ParseError::new(
+            line,
+            column,
+            "run count is missing its tag",
+        ));
+    }
+
+    let (width, height) = size.unwrap_or((width, height));
+    let universe = build_universe(width, height, &alive, last_line.max(1))?;
+
+    Ok(Pattern {
+        universe,
+        name,
+        rule,
+        comments,
+    })
+}
+
+/// The `x` and `y` sizes from the header, read without decoding any cells,
+/// or `None` when the data starts without a header.
+pub fn size(text: &str) -> Result<Option<(u32, u32)>, ParseError> {
+    for (line_idx, line) in text.lines().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('#') || trimmed.is_empty() {
+            continue;
+        }
+        if !trimmed.starts_with('x') {
+            return Ok(None);
+        }
+        let ((x, y), _) = parse_header(line, line_idx + 1)?;
+        return Ok(Some((x as u32, y as u32)));
+    }
+    Ok(None)
+}
+
+// Parses `x = m, y = n, rule = abc` into the size and optional rule
+fn parse_header(line: &str, line_no: usize) -> Result<((u64, u64), Option<String>), ParseError> {
+    let (mut x, mut y, mut rule) = (None, None, None);
+
+    let mut offset = 0;
+    for part in line.split(',') {
+        let start = offset;
+        let column = start + part.len() - part.trim_start().len() + 1;
+        offset += part.len() + 1;
+
+        let mut key_value = part.splitn(2, '=');
+        let key = key_value.next().unwrap().trim();
+        let value = match key_value.next() {
+            Some(value) => value.trim(),
+            None => {
+                return Err(ParseError::new(
+                    line_no,
+                    column,
+                    format!("expected `key = value` in header, found '{}'", part.trim()),
+                ));
+            }
+        };
+
+        match key {
+            "x" | "y" => {
+                let number = value.parse::<u32>().map_err(|_| {
+                    ParseError::new(line_no, column, format!("invalid {} size '{}'", key, value))
+                })?;
+                if key == "x" {
+                    x = Some(number as u64);
+                } else {
+                    y = Some(number as u64);
+                }
+            }
+            // The rule runs to the end of the line, since bounded grid
+            // suffixes like `B3/S23:T64,64` hold commas of their own
+            "rule" => {
+                let value_start = start + part.find('=').unwrap() + 1;
+                rule = Some(line[value_start..].trim().to_string());
+                break;
+            }
+            _ => {}
+        }
+    }
+
+    match (x, y) {
+        (Some(x), Some(y)) => Ok(((x, y), rule)),
+        _ => Err(ParseError::new(
+            line_no,
+            1,
+            "header needs both `x` and `y` sizes",
+        )),
+    }
+}
+
+pub fn write(pattern: &Pattern) -> String {
+    let universe = &pattern.universe;
+    let mut out = String::new();
+
+    if let Some(name) = pattern.name.as_ref() {
+        writeln!(out, "#N {}", name).unwrap();
+    }
+    for comment in pattern.comments.iter() {
+        writeln!(out, "#C {}", comment).unwrap();
+    }
+    writeln!(
+        out,
+        "x = {}, y = {}, rule = {}",
+        universe.width(),
+        universe.height(),
+        pattern.rule.as_deref().unwrap_or(DEFAULT_RULE)
+    )
+    .unwrap();
+
+    let mut tokens = vec![];
+    let mut last_row = None;
+    for row in 0..universe.height() {
+        let mut runs: Vec<(Cell, u32)> = vec![];
+        for col in 0..universe.width() {
+            let cell = universe.cells()[universe.get_index(row, col)];
+            match runs.last_mut() {
+                Some((state, length)) if *state == cell => *length += 1,
+                _ => runs.push((cell, 1)),
+            }
+        }
+        if let Some((Cell::Dead, _)) = runs.last() {
+            runs.pop();
+        }
+        if runs.is_empty() {
+            continue;
+        }
+
+        let skipped = match last_row {
+            Some(last_row) => row - last_row,
+            None => row,
+        };
+        if skipped > 0 {
+            tokens.push(token(skipped, '$'));
+        }
+        for (cell, length) in runs {
+            let tag = if cell == Cell::Alive { 'o' } else { 'b' };
+            tokens.push(token(length, tag));
+        }
+        last_row = Some(row);
+    }
+    tokens.push(String::from("!"));
+
+    let mut line_length = 0;
+    for token in tokens {
+        if line_length + token.len() > LINE_LENGTH {
+            out.push('\n');
+            line_length = 0;
+        }
+        line_length += token.len();
+        out.push_str(&token);
+    }
+    out.push('\n');
+
+    out
+}
+
+fn token(count: u32, tag: char) -> String {
+    if count == 1 {
+        tag.to_string()
+    } else {
+        format!("{}{}", count, tag)
+    }
+}
+
+impl Universe {
+    pub fn from_rle(text: &str) -> Result<Universe, ParseError> {
+        parse(text).map(|pattern| pattern.universe)
+    }
+
+    pub fn to_rle(&self) -> String {
+        write(&Pattern::from(self.clone()))
+    }
+}
@@ -0,0 +1,354 @@
+//! Turning bitmaps into boards.
+//!
+//! PBM and PGM, in both their plain (`P1`, `P2`) and raw (`P4`, `P5`)
+//! forms, are always supported. PNG needs the `png` feature. Every image is
+//! reduced to grayscale, optionally shrunk to fit the board, and cells darker
+//! than the threshold come alive.
+
+use std::error::Error;
+use std::fmt;
+
+use crate::formats::MAX_CELLS;
+use crate::universe::Universe;
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// A grayscale image, 0 for black and 255 for white.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    /// One byte per pixel, in row-major order.
+    pub pixels: Vec<u8>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ImportOptions {
+    /// Pixels darker than this come alive.
+    pub threshold: u8,
+    /// Brings pixels at or above the threshold alive instead, for light
+    /// drawings on a dark background.
+    pub invert: bool,
+    /// Largest (width, height) the board may have. Bigger images are
+    /// shrunk, keeping their aspect ratio.
+    pub fit: Option<(u32, u32)>,
+}
+
+impl Default for ImportOptions {
+    fn default() -> ImportOptions {
+        ImportOptions {
+            threshold: 128,
+            invert: false,
+            fit: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ImageError {
+    /// The bytes aren't in a format this build can read.
+    Unsupported(String),
+    Malformed(String),
+    TooLarge {
+        width: u32,
+        height: u32,
+    },
+}
+
+impl fmt::Display for ImageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ImageError::Unsupported(message) => write!(f, "unsupported image: {}", message),
+            ImageError::Malformed(message) => write!(f, "malformed image: {}", message),
+            ImageError::TooLarge { width, height } => {
+                write!(f, "{}x{} image is too large", width, height)
+            }
+        }
+    }
+}
+
+impl Error for ImageError {}
+
+fn malformed(message: &str) -> ImageError {
+    ImageError::Malformed(message.to_string())
+}
+
+fn check_size(width: u32, height: u32) -> Result<(), ImageError> {
+    if width as u64 * height as u64 > MAX_CELLS {
+        return Err(ImageError::TooLarge { width, height });
+    }
+    Ok(())
+}
+
+fn is_netpbm(bytes: &[u8]) -> bool {
+    matches!(bytes, [b'P', b'1' | b'2' | b'4' | b'5', space, ..] if space.is_ascii_whitespace())
+}
+
+/// Whether `bytes` start like an image `decode` knows, even if this build
+/// can't decode it.
+pub fn detect(bytes: &[u8]) -> bool {
+    is_netpbm(bytes) || bytes.starts_with(PNG_SIGNATURE)
+}
+
+/// Decodes a PBM, PGM or (with the `png` feature) PNG image.
+pub fn decode(bytes: &[u8]) -> Result<Image, ImageError> {
+    match bytes {
+        _ if is_netpbm(bytes) => parse_netpbm(bytes),
+        _ if bytes.starts_with(PNG_SIGNATURE) => decode_png(bytes),
+        _ => Err(ImageError::Unsupported(String::from(
+            "expected a PBM, PGM or PNG file",
+        ))),
+    }
+}
+
+impl Image {
+    /// Shrinks the image to fit within `max_width` x `max_height`, averaging
+    /// the pixels each new one covers. Images that already fit are returned
+    /// unchanged.
+    pub fn downscale(&self, max_width: u32, max_height: u32) -> Image {
+        if self.width <= max_width && self.height <= max_height {
+            return self.clone();
+        }
+        if self.pixels.is_empty() || max_width == 0 || max_height == 0 {
+            return Image {
+                width: self.width.min(max_width),
+                height: self.height.min(max_height),
+                pixels: vec![],
+            };
+        }
+
+        // Shrink both sides by the larger ratio so the aspect ratio holds
+        let (width, height) =
+            if self.width as u64 * max_height as u64 > self.height as u64 * max_width as u64 {
+                let height = self.height as u64 * max_width as u64 / self.width as u64;
+                (max_width, height.max(1) as u32)
+            } else {
+                let width = self.width as u64 * max_height as u64 / self.height as u64;
+                (width.max(1) as u32, max_height)
+            };
+
+        let span = |idx: u32, from: u32, to: u32| {
+            let start = idx as u64 * from as u64 / to as u64;
+            let end = (idx as u64 + 1) * from as u64 / to as u64;
+            start as u32..(end as u32).max(start as u32 + 1)
+        };
+        let mut pixels = Vec::with_capacity((width * height) as usize);
+        for row in 0..height {
+            for col in 0..width {
+                let (mut sum, mut count) = (0u64, 0u64);
+                for source_row in span(row, self.height, height) {
+                    for source_col in span(col, self.width, width) {
+                        sum += self.pixels[(source_row * self.width + source_col) as usize] as u64;
+                        count += 1;
+                    }
+                }
+                pixels.push((sum / count) as u8);
+            }
+        }
+
+        Image {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    pub fn to_universe(&self, threshold: u8, invert: bool) -> Universe {
+        let mut universe = Universe::empty(self.width, self.height);
+        let alive: Vec<(u32, u32)> = self
+            .pixels
+            .iter()
+            .enumerate()
+            .filter(|&(_, &pixel)| (pixel < threshold) != invert)
+            .map(|(idx, _)| (idx as u32 / self.width, idx as u32 % self.width))
+            .collect();
+        universe.set_cells(&alive);
+        universe
+    }
+}
+
+impl Universe {
+    pub fn from_image(bytes: &[u8], options: &ImportOptions) -> Result<Universe, ImageError> {
+        let mut image = decode(bytes)?;
+        if let Some((width, height)) = options.fit {
+            image = image.downscale(width, height);
+        }
+        Ok(image.to_universe(options.threshold, options.invert))
+    }
+}
+
+// Netpbm headers are whitespace separated numbers, with `#` comments
+struct Header<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Header<'a> {
+    fn skip_space(&mut self) {
+        while let Some(&byte) = self.bytes.get(self.position) {
+            if byte == b'#' {
+                while self.bytes.get(self.position).is_some_and(|&b| b != b'\n') {
+                    self.position += 1;
+                }
+            } else if byte.is_ascii_whitespace() {
+                self.position += 1;
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn number(&mut self, what: &str) -> Result<u32, ImageError> {
+        self.skip_space();
+        let start = self.position;
+        while self
+            .bytes
+            .get(self.position)
+            .is_some_and(u8::is_ascii_digit)
+        {
+            self.position += 1;
+        }
+        std::str::from_utf8(&self.bytes[start..self.position])
+            .unwrap()
+            .parse()
+            .map_err(|_| malformed(&format!("expected the {}", what)))
+    }
+
+    // Raw pixel data follows a single whitespace byte
+    fn raster(&mut self) -> Result<&'a [u8], ImageError> {
+        match self.bytes.get(self.position) {
+            Some(byte) if byte.is_ascii_whitespace() => Ok(&self.bytes[self.position + 1..]),
+            _ => Err(malformed("expected whitespace before the pixel data")),
+        }
+    }
+}
+
+fn parse_netpbm(bytes: &[u8]) -> Result<Image, ImageError> {
+    let kind = bytes[1];
+    let mut header = Header { bytes, position: 2 };
+    let width = header.number("width")?;
+    let height = header.number("height")?;
+    check_size(width, height)?;
+    let count = (width * height) as usize;
+
+    let pixels = match kind {
+        b'1' => {
+            // Plain PBM bits needn't be separated
+            let mut pixels = Vec::with_capacity(count);
+            while pixels.len() < count {
+                header.skip_space();
+                match header.bytes.get(header.position) {
+                    Some(b'0') => pixels.push(255),
+                    Some(b'1') => pixels.push(0),
+                    Some(_) => return Err(malformed("expected 0 or 1")),
+                    None => return Err(malformed("pixel data is truncated")),
+                }
+                header.position += 1;
+            }
+            pixels
+        }
+        b'4' => {
+            let raster = header.raster()?;
+            let row_bytes = width.div_ceil(8) as usize;
+            if raster.len() < row_bytes * height as usize {
+                return Err(malformed("pixel data is truncated"));
+            }
+            (0..count)
+                .map(|idx| {
+                    let (row, col) = (idx / width as usize, idx % width as usize);
+                    let byte = raster[row * row_bytes + col / 8];
+                    if byte & (0x80 >> (col % 8)) != 0 {
+                        0
+                    } else {
+                        255
+                    }
+                })
+                .collect()
+        }
+        _ => {
+            let max = header.number("maximum gray value")?;
+            if max == 0 || max > u16::MAX as u32 {
+                return Err(malformed("maximum gray value must be 1 to 65535"));
+            }
+            let scale = |value: u32| -> Result<u8, ImageError> {
+                if value > max {
+                    return Err(malformed("gray value is above the maximum"));
+                }
+                Ok((value * 255 / max) as u8)
+            };
+
+            let mut pixels = Vec::with_capacity(count);
+            if kind == b'2' {
+                for _ in 0..count {
+                    pixels.push(scale(header.number("gray value")?)?);
+                }
+            } else {
+                let raster = header.raster()?;
+                let depth = if max < 256 { 1 } else { 2 };
+                if raster.len() < count * depth {
+                    return Err(malformed("pixel data is truncated"));
+                }
+                for sample in raster.chunks(depth).take(count) {
+                    let value = sample.iter().fold(0, |acc, &byte| acc << 8 | byte as u32);
+                    pixels.push(scale(value)?);
+                }
+            }
+            pixels
+        }
+    };
+
+    Ok(Image {
+        width,
+        height,
+        pixels,
+    })
+}
+
+#[cfg(feature = "png")]
+fn decode_png(bytes: &[u8]) -> Result<Image, ImageError> {
+    use png::{ColorType, Decoder, Transformations};
+
+    let png_error = |error: png::DecodingError| ImageError::Malformed(error.to_string());
+
+    let mut decoder = Decoder::new(bytes);
+    decoder.set_transformations(Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().map_err(png_error)?;
+    let (width, height) = (reader.info().width, reader.info().height);
+    check_size(width, height)?;
+
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let frame = reader.next_frame(&mut buffer).map_err(png_error)?;
+    let channels = frame.color_type.samples();
+
+    let luma =
+        |rgb: &[u8]| (rgb[0] as u32 * 299 + rgb[1] as u32 * 587 + rgb[2] as u32 * 114) / 1000;
+    let pixels = buffer[..frame.buffer_size()]
+        .chunks(channels)
+        .take((width * height) as usize)
+        .map(|pixel| {
+            let (value, alpha) = match frame.color_type {
+                ColorType::Grayscale => (pixel[0] as u32, 255),
+                ColorType::GrayscaleAlpha => (pixel[0] as u32, pixel[1] as u32),
+                ColorType::Rgb => (luma(pixel), 255),
+                ColorType::Rgba => (luma(pixel), pixel[3] as u32),
+                // The transformations expand palettes to RGB(A)
+                ColorType::Indexed => (pixel[0] as u32, 255),
+            };
+            // Transparent pixels count as white paper
+            ((value * alpha + 255 * (255 - alpha)) / 255) as u8
+        })
+        .collect();
+
+    Ok(Image {
+        width,
+        height,
+        pixels,
+    })
+}
+
+#[cfg(not(feature = "png"))]
+fn decode_png(_bytes: &[u8]) -> Result<Image, ImageError> {
+    Err(ImageError::Unsupported(String::from(
+        "PNG support not compiled in",
+    )))
+}
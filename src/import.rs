@@ -0,0 +1,93 @@
+//! Reading files dropped onto the page, whatever they hold.
+
+use std::error::Error;
+use std::fmt;
+
+use crate::formats::{self, macrocell, Macrocell, ParseError, Pattern, PatternFormat};
+use crate::image::{self, ImageError, ImportOptions};
+use crate::universe::Universe;
+
+/// Extensions read as images rather than pattern text.
+pub const IMAGE_EXTENSIONS: &[&str] = &["pbm", "pgm", "png"];
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ImportError {
+    Pattern(ParseError),
+    Image(ImageError),
+    /// The file is neither an image nor a pattern format we know.
+    Unrecognized,
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ImportError::Pattern(error) => write!(f, "{}", error),
+            ImportError::Image(error) => write!(f, "{}", error),
+            ImportError::Unrecognized => write!(f, "not a pattern file or image"),
+        }
+    }
+}
+
+impl Error for ImportError {}
+
+impl From<ParseError> for ImportError {
+    fn from(error: ParseError) -> ImportError {
+        ImportError::Pattern(error)
+    }
+}
+
+impl From<ImageError> for ImportError {
+    fn from(error: ImageError) -> ImportError {
+        ImportError::Image(error)
+    }
+}
+
+/// Reads the file called `name`. Images are converted with `options`;
+/// anything else is parsed as whichever pattern format its contents look
+/// like, or failing that, the one its extension suggests. Macrocell patterns
+/// are cut down to `options.fit` from the top left of their live cells.
+pub fn read_file(
+    name: &str,
+    bytes: &[u8],
+    options: &ImportOptions,
+) -> Result<Pattern, ImportError> {
+    let extension = name
+        .rsplit_once('.')
+        .map(|(_, extension)| extension.to_ascii_lowercase())
+        .unwrap_or_default();
+
+    if IMAGE_EXTENSIONS.contains(&extension.as_str()) || image::detect(bytes) {
+        return Ok(Pattern::from(Universe::from_image(bytes, options)?));
+    }
+
+    let text = std::str::from_utf8(bytes).map_err(|_| ImportError::Unrecognized)?;
+    let format = formats::detect(text)
+        .or_else(|| formats::by_extension(&extension))
+        .ok_or(ImportError::Unrecognized)?;
+    match options.fit {
+        Some(fit) if format.name() == Macrocell.name() => read_macrocell(text, fit),
+        _ => Ok(format.parse(text)?),
+    }
+}
+
+// Keeps the pattern as a quadtree and copies out only the window that fits,
+// so huge macrocells load without being expanded
+fn read_macrocell(text: &str, (width, height): (u32, u32)) -> Result<Pattern, ImportError> {
+    let macrocell = macrocell::parse(text)?;
+    let universe = match macrocell.tree.bounding_box() {
+        Some((row, column, box_height, box_width)) => macrocell.tree.window(
+            row,
+            column,
+            box_height.min(height as u64) as u32,
+            box_width.min(width as u64) as u32,
+        ),
+        None => Universe::empty(0, 0),
+    };
+
+    Ok(Pattern {
+        universe,
+        name: None,
+        rule: macrocell.rule,
+        comments: macrocell.comments,
+    })
+}
@@ -1,14 +1,48 @@
 use std::cell::RefCell;
 use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsCast;
 
-mod cell;
+pub mod apgcode;
+mod base64;
+pub mod cell;
+pub mod census;
+pub mod checksum;
+pub mod cycle;
+pub mod export;
+pub mod formats;
 mod fps;
-mod universe;
+pub mod image;
+pub mod import;
+pub mod library;
+pub mod population;
+pub mod predecessor;
+pub mod quadtree;
+pub mod search;
+#[cfg(feature = "serde")]
+mod serialize;
+pub mod share;
+pub mod snapshot;
+pub mod storage;
+pub mod universe;
 mod utils;
 
-use crate::universe::Universe;
+use crate::census::Census;
+use crate::cycle::{Cycle, CycleDetector};
+use crate::export::gif::GifOptions;
+use crate::export::RenderOptions;
+use crate::formats::{rle, Pattern};
+use crate::image::ImportOptions;
+use crate::population::{MarkerKind, PopulationHistory};
+use crate::predecessor::{PredecessorOptions, PredecessorSearch, Progress};
+use crate::search::{Match, SearchOptions};
+use crate::share::SharedBoard;
+use crate::snapshot::Snapshot;
+use crate::storage::SavedBoard;
+use crate::universe::{Region, StampMode, Universe};
 use crate::utils::{cancel_animation_frame, element_by_id, request_animation_frame, window};
 
 // When the `wee_alloc` feature is enabled, use `wee_alloc` as the global
@@ -17,23 +51,26 @@ use crate::utils::{cancel_animation_frame, element_by_id, request_animation_fram
 #[global_allocator]
 static ALLOC: wee_alloc::WeeAlloc = wee_alloc::WeeAlloc::INIT;
 
-// A macro to provide `println!(..)`-style syntax for `console.log` logging.
-macro_rules! log {
-    ( $( $t:tt )* ) => {
-        web_sys::console::log_1(&format!( $( $t )* ).into());
-    }
-}
-
 static CELL_SIZE: u32 = 10; // px
 static GRID_COLOR: &str = "#CCCCCC";
 static DEAD_COLOR: &str = "#FFFFFF";
 static ALIVE_COLOR: &str = "#000000";
 static HOVER_COLOR: &str = "#FF5500";
+static SELECTION_COLOR: &str = "rgba(0, 120, 255, 0.3)";
+static MATCH_COLOR: &str = "rgba(0, 200, 80, 0.4)";
+static GIF_FRAMES: u32 = 60;
+static PAUSE_MARKER_COLOR: &str = "#888888";
+static EDIT_MARKER_COLOR: &str = HOVER_COLOR;
+static GRAPH_LEFT_MARGIN: f64 = 32.0; // px
+static GRAPH_BOTTOM_MARGIN: f64 = 14.0; // px
+static PREDECESSOR_TIME_LIMIT: Duration = Duration::from_secs(30);
+static PREDECESSOR_NODES_PER_FRAME: u64 = 20_000;
 
 #[wasm_bindgen]
 pub fn run() -> Result<(), JsValue> {
+    utils::set_panic_hook();
     let mut fps = fps::Fps::new();
-    let universe = Rc::new(RefCell::new(Universe::new()));
+    let universe = Rc::new(RefCell::new(initial_universe()));
 
     let button = element_by_id("play-pause").dyn_into::<web_sys::HtmlButtonElement>()?;
     let button = Rc::new(button);
@@ -42,10 +79,7 @@ pub fn run() -> Result<(), JsValue> {
     let canvas = canvas_elem
         .clone()
         .dyn_into::<web_sys::HtmlCanvasElement>()?;
-    let height = universe.borrow().height();
-    let width = universe.borrow().width();
-    canvas.set_height((CELL_SIZE + 1) * height + 1);
-    canvas.set_width((CELL_SIZE + 1) * width + 1);
+    fit_canvas(&universe.borrow());
 
     let context = canvas
         .get_context("2d")?
@@ -53,9 +87,111 @@ pub fn run() -> Result<(), JsValue> {
         .dyn_into::<web_sys::CanvasRenderingContext2d>()?;
     let context = Rc::new(context);
 
-    add_clear_handler(Rc::clone(&context), Rc::clone(&universe));
+    build_palette()?;
+
+    let hover = Rc::new(RefCell::new(Hover::default()));
+    let selection: Rc<RefCell<Option<Region>>> = Rc::new(RefCell::new(None));
+    let history = Rc::new(RefCell::new(PopulationHistory::default()));
+
+    add_clear_handler(
+        Rc::clone(&context),
+        Rc::clone(&universe),
+        Rc::clone(&history),
+    );
+    add_speed_handler(Rc::clone(&universe))?;
+    add_slot_handlers(
+        Rc::clone(&context),
+        Rc::clone(&universe),
+        Rc::clone(&history),
+    )?;
+
+    {
+        let history = Rc::clone(&history);
+        add_button_handler("save-population", move || {
+            let csv = history.borrow().to_csv();
+            if let Err(error) = utils::download("population.csv", csv.as_bytes(), "text/csv") {
+                show_status(&format!("Couldn't save the population: {:?}", error));
+            }
+        });
+    }
+
+    {
+        let universe = Rc::clone(&universe);
+        add_button_handler("census", move || {
+            let census = universe.borrow().census();
+            if let Err(error) = show_census(&census) {
+                show_status(&format!("Couldn't show the census: {:?}", error));
+                return;
+            }
+            show_status(&format!("{} objects", census.objects.len()));
+        });
+    }
+
+    {
+        let universe = Rc::clone(&universe);
+        add_button_handler("save-image", move || {
+            let universe = universe.borrow();
+            let png = match universe.to_png(&RenderOptions::default()) {
+                Ok(png) => png,
+                Err(error) => {
+                    show_status(&format!("Couldn't save the image: {}", error));
+                    return;
+                }
+            };
+            let name = format!("generation-{}.png", universe.generation());
+            if let Err(error) = utils::download(&name, &png, "image/png") {
+                show_status(&format!("Couldn't save the image: {:?}", error));
+            }
+        });
+    }
+
+    {
+        let universe = Rc::clone(&universe);
+        add_button_handler("save-svg", move || {
+            let universe = universe.borrow();
+            let svg = universe.to_svg(&RenderOptions::default());
+            let name = format!("generation-{}.svg", universe.generation());
+            if let Err(error) = utils::download(&name, svg.as_bytes(), "image/svg+xml") {
+                show_status(&format!("Couldn't save the SVG: {:?}", error));
+            }
+        });
+    }
+
+    // Records the next generations of the selection, or the whole board
+    {
+        let universe = Rc::clone(&universe);
+        let selection = Rc::clone(&selection);
+        add_button_handler("save-gif", move || {
+            let universe = universe.borrow();
+            let options = GifOptions {
+                frames: GIF_FRAMES,
+                crop: *selection.borrow(),
+                ..GifOptions::default()
+            };
+            let name = format!("generation-{}.gif", universe.generation());
+            let result = universe
+                .to_gif(&options)
+                .map_err(|error| format!("{}", error))
+                .and_then(|gif| {
+                    utils::download(&name, &gif, "image/gif")
+                        .map_err(|error| format!("{:?}", error))
+                });
+            if let Err(error) = result {
+                show_status(&format!("Couldn't save the GIF: {}", error));
+            }
+        });
+    }
 
     let animation_id = Rc::new(RefCell::new(0));
+    let detector = Rc::new(RefCell::new(CycleDetector::default()));
+    add_cycle_handler(
+        &canvas,
+        Rc::clone(&universe),
+        Rc::clone(&detector),
+        Rc::clone(&history),
+        Rc::clone(&animation_id),
+    )?;
+
     let recursive_render_loop = Rc::new(RefCell::new(None));
     let outer_render_loop = Rc::clone(&recursive_render_loop);
 
@@ -64,16 +200,40 @@ pub fn run() -> Result<(), JsValue> {
         let animation_id = Rc::clone(&animation_id);
         let universe = Rc::clone(&universe);
         let context = Rc::clone(&context);
+        let selection = Rc::clone(&selection);
+        let detector = Rc::clone(&detector);
+        let history = Rc::clone(&history);
+        let canvas = canvas.clone();
 
         *outer_render_loop.borrow_mut() = Some(Closure::wrap(Box::new(move || {
             fps.render();
-            draw_grid(&context, &*universe.borrow());
-            draw_cells(&context, &*universe.borrow());
+            draw_grid(&context, &universe.borrow());
+            draw_cells(&context, &universe.borrow());
+            draw_selection(&context, *selection.borrow());
 
-            universe.borrow_mut().tick();
+            // Seeing the board before ticking notices edits made since the
+            // last frame
+            let mut detector_ref = detector.borrow_mut();
+            detector_ref.observe(&universe.borrow());
+            let mut cycle = None;
+            for _ in 0..selected_speed() {
+                universe.borrow_mut().tick();
+                history.borrow_mut().record(&universe.borrow());
+                cycle = detector_ref.observe(&universe.borrow());
+                if cycle.is_some() {
+                    break;
+                }
+            }
+            drop(detector_ref);
+            draw_population(&history.borrow());
 
             *animation_id.borrow_mut() =
                 request_animation_frame(recursive_render_loop.borrow().as_ref().unwrap());
+
+            // Fired last so a listener can pause the frame just requested
+            if let Some(cycle) = cycle {
+                dispatch_cycle_event(&canvas, cycle);
+            }
         }) as Box<dyn FnMut()>));
     }
 
@@ -82,6 +242,8 @@ pub fn run() -> Result<(), JsValue> {
         let animation_id = Rc::clone(&animation_id);
         let outer_render_loop = Rc::clone(&outer_render_loop);
         let button_copy = Rc::clone(&button);
+        let universe = Rc::clone(&universe);
+        let history = Rc::clone(&history);
 
         let toggle_play_pause = Closure::wrap(Box::new(move || {
             if *animation_id.borrow() == 0 {
@@ -92,6 +254,8 @@ pub fn run() -> Result<(), JsValue> {
                 button_copy.set_inner_text("▶");
                 cancel_animation_frame(*animation_id.borrow());
                 *animation_id.borrow_mut() = 0;
+                mark_population(&history, &universe.borrow(), MarkerKind::Pause);
+                save_session(&universe.borrow());
             }
         }) as Box<dyn FnMut()>);
 
@@ -102,27 +266,28 @@ pub fn run() -> Result<(), JsValue> {
     // Handles cell clicking
     {
         let universe = Rc::clone(&universe);
-        let canvas_elem = canvas_elem.clone();
         let canvas_copy = canvas.clone();
         let context = Rc::clone(&context);
+        let hover = Rc::clone(&hover);
+        let selection = Rc::clone(&selection);
+        let history = Rc::clone(&history);
 
         let cell_click_handler = Closure::wrap(Box::new(move |event: web_sys::MouseEvent| {
-            let bounding_rect = canvas_elem.get_bounding_client_rect();
-            let scale_x = canvas_copy.width() as f64 / bounding_rect.width();
-            let scale_y = canvas_copy.height() as f64 / bounding_rect.height();
-
-            let canvas_left = (event.client_x() as f64 - bounding_rect.left()) * scale_x;
-            let canvas_top = (event.client_y() as f64 - bounding_rect.top()) * scale_y;
-
-            let row = (canvas_top / (CELL_SIZE as f64 + 1f64))
-                .floor()
-                .min(height as f64 - 1f64) as u32;
-            let col = (canvas_left / (CELL_SIZE as f64 + 1f64))
-                .floor()
-                .min(width as f64 - 1f64) as u32;
-
-            universe.borrow_mut().toggle_cell(row, col);
-            draw_cells(&context, &*universe.borrow());
+            // Shift-clicks belong to the selection handlers
+            if event.shift_key() {
+                return;
+            }
+
+            if hover.borrow().pattern.is_some() {
+                place_hover(&mut universe.borrow_mut(), &mut hover.borrow_mut());
+            } else {
+                let (row, col) = event_cell(&canvas_copy, &event, &universe.borrow());
+                universe.borrow_mut().toggle_cell(row, col);
+            }
+            draw_cells(&context, &universe.borrow());
+            mark_population(&history, &universe.borrow(), MarkerKind::Edit);
+            save_session(&universe.borrow());
+            draw_selection(&context, *selection.borrow());
         }) as Box<dyn FnMut(_)>);
         canvas.add_event_listener_with_callback(
             "click",
@@ -132,12 +297,23 @@ pub fn run() -> Result<(), JsValue> {
     }
 
     add_drag_handlers(
-        canvas_elem.clone(),
+        canvas.clone(),
         Rc::clone(&context),
         Rc::clone(&universe),
+        Rc::clone(&hover),
         Rc::clone(&animation_id),
     );
 
+    add_selection_handlers(
+        canvas.clone(),
+        Rc::clone(&context),
+        Rc::clone(&universe),
+        Rc::clone(&hover),
+        Rc::clone(&selection),
+        Rc::clone(&history),
+        Rc::clone(&animation_id),
+    )?;
+
     // Starts simulation
     {
         let animation_id = Rc::clone(&animation_id);
@@ -149,118 +325,369 @@ pub fn run() -> Result<(), JsValue> {
     Ok(())
 }
 
+/// Fills `#census-table` with a row for each kind of object in `census`.
+fn show_census(census: &Census) -> Result<(), JsValue> {
+    let document = utils::document();
+    let table = element_by_id("census-table");
+    table.set_inner_html("");
+
+    let mut rows = vec![[
+        String::from("Count"),
+        String::from("Object"),
+        String::from("apgcode"),
+    ]];
+    rows.extend(census.entries().into_iter().map(|entry| {
+        [
+            entry.count.to_string(),
+            entry.name.unwrap_or("").to_string(),
+            entry.apgcode.unwrap_or_else(|| String::from("(unsettled)")),
+        ]
+    }));
+    for (idx, columns) in rows.iter().enumerate() {
+        let row = document.create_element("tr")?;
+        for column in columns.iter() {
+            let cell = document.create_element(if idx == 0 { "th" } else { "td" })?;
+            cell.set_text_content(Some(column));
+            row.append_child(&cell)?;
+        }
+        table.append_child(&row)?;
+    }
+
+    Ok(())
+}
+
+/// Announces a repeating board with a `cycle` event on the canvas. Its
+/// `detail` holds the cycle's `start`, `period`, `population`, displacement
+/// `dx` and `dy`, and a `description` such as "period-2 oscillator".
+fn dispatch_cycle_event(canvas: &web_sys::HtmlCanvasElement, cycle: Cycle) {
+    let detail = js_sys::Object::new();
+    let fields = [
+        ("start", JsValue::from_f64(cycle.start as f64)),
+        ("period", JsValue::from_f64(cycle.period as f64)),
+        ("population", JsValue::from_f64(cycle.population as f64)),
+        ("dx", JsValue::from_f64(cycle.displacement.0 as f64)),
+        ("dy", JsValue::from_f64(cycle.displacement.1 as f64)),
+        ("description", JsValue::from_str(&cycle.to_string())),
+    ];
+    for (key, value) in fields.iter() {
+        js_sys::Reflect::set(&detail, &JsValue::from_str(key), value).unwrap();
+    }
+
+    let init = web_sys::CustomEventInit::new();
+    init.set_detail(&detail);
+    let event = web_sys::CustomEvent::new_with_event_init_dict("cycle", &init).unwrap();
+    canvas.dispatch_event(&event).unwrap();
+}
+
+/// Reports cycles in the status area, pausing first when "Pause when
+/// settled" is checked.
+fn add_cycle_handler(
+    canvas: &web_sys::HtmlCanvasElement,
+    universe: Rc<RefCell<Universe>>,
+    detector: Rc<RefCell<CycleDetector>>,
+    history: Rc<RefCell<PopulationHistory>>,
+    animation_id: Rc<RefCell<i32>>,
+) -> Result<(), JsValue> {
+    let cycle_handler = Closure::wrap(Box::new(move || {
+        let cycle = match detector.borrow().cycle() {
+            Some(cycle) => cycle,
+            None => return,
+        };
+        if auto_pause_enabled() {
+            pause(&animation_id);
+            mark_population(&history, &universe.borrow(), MarkerKind::Pause);
+            save_session(&universe.borrow());
+        }
+        match cycle.velocity() {
+            Some(velocity) => show_status(&format!(
+                "Generation {}: {} heading {}, moving ({}, {}) every {} generations",
+                cycle.start,
+                cycle,
+                velocity.heading(),
+                velocity.dx,
+                velocity.dy,
+                velocity.period
+            )),
+            None => show_status(&format!("Generation {}: {}", cycle.start, cycle)),
+        }
+    }) as Box<dyn FnMut()>);
+    canvas.add_event_listener_with_callback("cycle", cycle_handler.as_ref().unchecked_ref())?;
+    cycle_handler.forget();
+    Ok(())
+}
+
+fn auto_pause_enabled() -> bool {
+    element_by_id("auto-pause")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .unwrap()
+        .checked()
+}
+
+/// Fills `#patterns` with a draggable thumbnail for every library pattern,
+/// grouped by category. The page's `dragStart` puts the `data-key` in the
+/// drag payload.
+fn build_palette() -> Result<(), JsValue> {
+    let document = utils::document();
+    let palette = element_by_id("patterns");
+
+    for &category in library::CATEGORIES.iter() {
+        let heading = document.create_element("h3")?;
+        heading.set_text_content(Some(category.name()));
+        palette.append_child(&heading)?;
+
+        for pattern in library::in_category(category) {
+            let item = document.create_element("div")?;
+            item.set_attribute("draggable", "true")?;
+            item.set_attribute("data-key", pattern.key)?;
+            item.set_attribute("ondragstart", "dragStart(event)")?;
+            item.set_attribute("ondragend", "dragEnd(event)")?;
+            item.set_attribute("title", pattern.name)?;
+
+            let label = document.create_element("div")?;
+            label.set_text_content(Some(pattern.name));
+            item.append_child(&label)?;
+
+            let thumbnail = document.create_element("div")?;
+            thumbnail.set_inner_html(&pattern.universe().to_svg(&RenderOptions::default()));
+            item.append_child(&thumbnail)?;
+
+            palette.append_child(&item)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// A pattern floating over the canvas until it is placed, either dragged in
+/// from the palette or pasted from the selection clipboard.
+#[derive(Default)]
+struct Hover {
+    pattern: Option<Universe>,
+    // Board cell the pattern's top left corner is over, may be off the board
+    origin: Option<(i32, i32)>,
+    // Last pointer position in fractional (row, column) cell units
+    position: Option<(f64, f64)>,
+    painted_cells: Vec<(u32, u32)>,
+}
+
+fn pause(animation_id: &RefCell<i32>) {
+    if *animation_id.borrow() == 0 {
+        return;
+    }
+    cancel_animation_frame(*animation_id.borrow());
+    *animation_id.borrow_mut() = 0;
+    element_by_id("play-pause")
+        .dyn_into::<web_sys::HtmlButtonElement>()
+        .unwrap()
+        .set_inner_text("▶");
+}
+
+// Pointer position over the canvas in fractional (row, column) cell units
+fn event_position(canvas: &web_sys::HtmlCanvasElement, event: &web_sys::MouseEvent) -> (f64, f64) {
+    let bounding_rect = canvas.get_bounding_client_rect();
+    let scale_x = canvas.width() as f64 / bounding_rect.width();
+    let scale_y = canvas.height() as f64 / bounding_rect.height();
+
+    let canvas_left = (event.client_x() as f64 - bounding_rect.left()) * scale_x;
+    let canvas_top = (event.client_y() as f64 - bounding_rect.top()) * scale_y;
+
+    (
+        canvas_top / (CELL_SIZE as f64 + 1f64),
+        canvas_left / (CELL_SIZE as f64 + 1f64),
+    )
+}
+
+fn event_cell(
+    canvas: &web_sys::HtmlCanvasElement,
+    event: &web_sys::MouseEvent,
+    universe: &Universe,
+) -> (u32, u32) {
+    let (row, col) = event_position(canvas, event);
+    let row = row.floor().max(0f64).min(universe.height() as f64 - 1f64) as u32;
+    let col = col.floor().max(0f64).min(universe.width() as f64 - 1f64) as u32;
+    (row, col)
+}
+
+/// Repaints the hovering pattern centered on `position`.
+fn paint_hover(
+    context: &web_sys::CanvasRenderingContext2d,
+    universe: &Universe,
+    hover: &mut Hover,
+    position: (f64, f64),
+) {
+    reset_cells(context, universe, &hover.painted_cells);
+    hover.painted_cells.clear();
+    hover.position = Some(position);
+
+    let prefab = match hover.pattern.as_ref() {
+        Some(prefab) => prefab,
+        None => return,
+    };
+    let prefab_height = prefab.height();
+    let prefab_width = prefab.width();
+    let height = universe.height();
+    let width = universe.width();
+    // Nothing to wrap around on an empty board
+    if width == 0 || height == 0 {
+        return;
+    }
+
+    let row = position.0 - ((prefab_height / 2) as f64).floor();
+    let row = if prefab_height % 2 == 1 {
+        row.floor()
+    } else {
+        row.round()
+    };
+    let row = row as i32;
+    let col = position.1 - ((prefab_width / 2) as f64).floor();
+    let col = if prefab_width % 2 == 1 {
+        col.floor()
+    } else {
+        col.round()
+    };
+    let col = col as i32;
+    hover.origin = Some((row, col));
+
+    context.set_fill_style_str(HOVER_COLOR);
+    for prefab_row in 0..prefab_height {
+        for prefab_col in 0..prefab_width {
+            let idx = prefab.get_index(prefab_row, prefab_col);
+            if prefab.cells()[idx] != cell::Cell::Alive {
+                continue;
+            }
+
+            // Wrap the preview around the torus like the stamp will
+            let board_row = (row + prefab_row as i32).rem_euclid(height as i32) as u32;
+            let board_col = (col + prefab_col as i32).rem_euclid(width as i32) as u32;
+            context.fill_rect(
+                (board_col * (CELL_SIZE + 1) + 1) as f64,
+                (board_row * (CELL_SIZE + 1) + 1) as f64,
+                CELL_SIZE as f64,
+                CELL_SIZE as f64,
+            );
+            hover.painted_cells.push((board_row, board_col));
+        }
+    }
+}
+
+/// Stamps the hovering pattern where it was last painted and drops it.
+fn place_hover(universe: &mut Universe, hover: &mut Hover) {
+    if let (Some(prefab), Some((row, col))) = (hover.pattern.as_ref(), hover.origin) {
+        universe.stamp(prefab, row, col, selected_stamp_mode());
+    }
+    *hover = Hover::default();
+}
+
 fn add_drag_handlers(
-    canvas_elem: web_sys::Element,
+    canvas: web_sys::HtmlCanvasElement,
     context: Rc<web_sys::CanvasRenderingContext2d>,
     universe: Rc<RefCell<Universe>>,
+    hover: Rc<RefCell<Hover>>,
     animation_id: Rc<RefCell<i32>>,
 ) {
     let window = window();
-    let canvas_html_elem = canvas_elem
-        .clone()
-        .dyn_into::<web_sys::HtmlElement>()
-        .unwrap();
-
-    let canvas = canvas_elem
-        .clone()
-        .dyn_into::<web_sys::HtmlCanvasElement>()
-        .unwrap();
-    let height = universe.borrow().height();
-    let width = universe.borrow().width();
-    let prefab_universe: Rc<RefCell<Option<Universe>>> = Rc::new(RefCell::new(None));
 
     {
-        let prefab_universe = Rc::clone(&prefab_universe);
+        let hover = Rc::clone(&hover);
+        let animation_id = Rc::clone(&animation_id);
         let drag_start_handler = Closure::wrap(Box::new(move |event: web_sys::DragEvent| {
-            if *animation_id.borrow() != 0 {
-                // TODO: update button text
-                cancel_animation_frame(*animation_id.borrow());
-                *animation_id.borrow_mut() = 0;
-                element_by_id("play-pause").dyn_into::<web_sys::HtmlButtonElement>().unwrap().set_inner_text("▶");
-            }
-            let src_id = event
+            let key = event
                 .data_transfer()
                 .unwrap()
                 .get_data("text/plain")
                 .unwrap();
-            let elem = utils::html_element_by_id(src_id.as_str());
-            *prefab_universe.borrow_mut() = Some(Universe::from(elem));
+            if let Some(pattern) = library::get(&key) {
+                pause(&animation_id);
+                hover.borrow_mut().pattern = Some(pattern.universe());
+            }
         }) as Box<dyn FnMut(_)>);
         // TODO: can this be canvas instead of window?
         window.set_ondragstart(Some(drag_start_handler.as_ref().unchecked_ref()));
         drag_start_handler.forget();
     }
 
-    let painted_cells: Rc<RefCell<Vec<(u32, u32)>>> = Rc::new(RefCell::new(vec![]));
+    // R rotates and F flips the hovering pattern, Escape drops it
+    {
+        let context = Rc::clone(&context);
+        let universe = Rc::clone(&universe);
+        let hover = Rc::clone(&hover);
+        let key_handler = Closure::wrap(Box::new(move |event: web_sys::KeyboardEvent| {
+            let mut hover = hover.borrow_mut();
+            let prefab = match hover.pattern.as_ref() {
+                Some(prefab) => prefab,
+                None => return,
+            };
+            let transformed = match event.key().as_str() {
+                "r" => prefab.rotate_90(),
+                "R" => prefab.rotate_270(),
+                "f" | "F" => prefab.flip_horizontal(),
+                "Escape" => {
+                    reset_cells(&context, &universe.borrow(), &hover.painted_cells);
+                    *hover = Hover::default();
+                    return;
+                }
+                _ => return,
+            };
+            hover.pattern = Some(transformed);
+            if let Some(position) = hover.position {
+                paint_hover(&context, &universe.borrow(), &mut hover, position);
+            }
+        }) as Box<dyn FnMut(_)>);
+        window
+            .add_event_listener_with_callback("keydown", key_handler.as_ref().unchecked_ref())
+            .unwrap();
+        key_handler.forget();
+    }
 
     {
+        let canvas_copy = canvas.clone();
         let context = Rc::clone(&context);
         let universe = Rc::clone(&universe);
-        let prefab_universe = Rc::clone(&prefab_universe);
-        let painted_cells = Rc::clone(&painted_cells);
+        let hover = Rc::clone(&hover);
 
-        // TODO: needs edge logic for shapes that wrap around world on borders
         let drag_over_handler = Closure::wrap(Box::new(move |event: web_sys::DragEvent| {
             event.dyn_ref::<web_sys::Event>().unwrap().prevent_default();
-            reset_cells(&context, &universe.borrow(), &painted_cells.borrow());
-            painted_cells.borrow_mut().clear();
-
-            let bounding_rect = canvas_elem.get_bounding_client_rect();
-            let scale_x = canvas.width() as f64 / bounding_rect.width();
-            let scale_y = canvas.height() as f64 / bounding_rect.height();
-
-            let canvas_left = (event.client_x() as f64 - bounding_rect.left()) * scale_x;
-            let canvas_top = (event.client_y() as f64 - bounding_rect.top()) * scale_y;
+            let position = event_position(&canvas_copy, &event);
+            paint_hover(
+                &context,
+                &universe.borrow(),
+                &mut hover.borrow_mut(),
+                position,
+            );
+        }) as Box<dyn FnMut(_)>);
+        canvas.set_ondragover(Some(drag_over_handler.as_ref().unchecked_ref()));
+        drag_over_handler.forget();
+    }
 
-            let prefab_height = prefab_universe.borrow().as_ref().unwrap().height();
-            let prefab_width = prefab_universe.borrow().as_ref().unwrap().width();
+    // Pasted patterns follow the mouse until clicked into place
+    {
+        let canvas_copy = canvas.clone();
+        let context = Rc::clone(&context);
+        let universe = Rc::clone(&universe);
+        let hover = Rc::clone(&hover);
 
-            let row = (canvas_top / (CELL_SIZE + 1) as f64) - ((prefab_height / 2) as f64).floor();
-            let row = if prefab_height % 2 == 1 {
-                row.floor()
-            } else {
-                row.round()
-            };
-            let row = row.min(height as f64 - 1f64) as u32;
-            let col = (canvas_left / (CELL_SIZE + 1) as f64) - ((prefab_width / 2) as f64).floor();
-            let col = if prefab_width % 2 == 1 {
-                col.floor()
-            } else {
-                col.round()
-            };
-            let col = col.min(width as f64 - 1f64) as u32;
-
-            let hover_style = JsValue::from(String::from(HOVER_COLOR));
-            context.set_fill_style(&hover_style);
-            for prefab_row in 0..prefab_height {
-                for prefab_col in 0..prefab_width {
-                    let idx = prefab_universe
-                        .borrow()
-                        .as_ref()
-                        .unwrap()
-                        .get_index(prefab_row, prefab_col);
-                    if prefab_universe.borrow().as_ref().unwrap().cells()[idx] == cell::Cell::Alive
-                    {
-                        context.fill_rect(
-                            ((col + prefab_col) * (CELL_SIZE + 1) + 1) as f64,
-                            ((row + prefab_row) * (CELL_SIZE + 1) + 1) as f64,
-                            CELL_SIZE as f64,
-                            CELL_SIZE as f64,
-                        );
-                        painted_cells
-                            .borrow_mut()
-                            .push((row + prefab_row, col + prefab_col));
-                    }
-                }
+        let mouse_move_handler = Closure::wrap(Box::new(move |event: web_sys::MouseEvent| {
+            if hover.borrow().pattern.is_none() {
+                return;
             }
-
-            // TODO: need to return false?
-            // false
+            let position = event_position(&canvas_copy, &event);
+            paint_hover(
+                &context,
+                &universe.borrow(),
+                &mut hover.borrow_mut(),
+                position,
+            );
         }) as Box<dyn FnMut(_)>);
-        canvas_html_elem.set_ondragover(Some(drag_over_handler.as_ref().unchecked_ref()));
-        drag_over_handler.forget();
+        canvas
+            .add_event_listener_with_callback(
+                "mousemove",
+                mouse_move_handler.as_ref().unchecked_ref(),
+            )
+            .unwrap();
+        mouse_move_handler.forget();
     }
 
+    let canvas_copy = canvas.clone();
     let drop_handler = Closure::wrap(Box::new(move |event: web_sys::DragEvent| {
         event.dyn_ref::<web_sys::Event>().unwrap().prevent_default();
         event
@@ -268,31 +695,777 @@ fn add_drag_handlers(
             .unwrap()
             .stop_propagation();
 
-        for (row, col) in painted_cells.borrow().iter() {
-            // TODO: this shouldn't be toggle
-            universe.borrow_mut().toggle_cell(*row, *col);
+        // Files dragged in from outside the page become the hovering prefab
+        let file = event
+            .data_transfer()
+            .and_then(|data| data.files())
+            .and_then(|files| files.get(0));
+        if let Some(file) = file {
+            pause(&animation_id);
+            load_dropped_file(
+                &file,
+                event_position(&canvas_copy, &event),
+                Rc::clone(&context),
+                Rc::clone(&universe),
+                Rc::clone(&hover),
+            );
+            return;
+        }
+
+        // Keyboard events aren't sent during a drag, so palette patterns keep
+        // floating after the drop where R and F can turn them, like pastes
+        if hover.borrow().pattern.is_some() {
+            let position = event_position(&canvas_copy, &event);
+            paint_hover(
+                &context,
+                &universe.borrow(),
+                &mut hover.borrow_mut(),
+                position,
+            );
+            show_status("Click to place the pattern, R rotates and F flips it");
         }
-        // TODO: rename func
-        reset_cells(&context, &universe.borrow(), &painted_cells.borrow());
-        painted_cells.borrow_mut().clear();
-        *prefab_universe.borrow_mut() = None;
     }) as Box<dyn FnMut(_)>);
-    canvas_html_elem.set_ondrop(Some(drop_handler.as_ref().unchecked_ref()));
+    canvas.set_ondrop(Some(drop_handler.as_ref().unchecked_ref()));
     drop_handler.forget();
 
     // TODO: add drag end handler
 }
 
+/// Reads a pattern file or image and floats it over the board at
+/// `position`, ready to be clicked into place.
+fn load_dropped_file(
+    file: &web_sys::File,
+    position: (f64, f64),
+    context: Rc<web_sys::CanvasRenderingContext2d>,
+    universe: Rc<RefCell<Universe>>,
+    hover: Rc<RefCell<Hover>>,
+) {
+    let reader = Rc::new(web_sys::FileReader::new().unwrap());
+    let reader_copy = Rc::clone(&reader);
+    let name = file.name();
+
+    let load_handler = Closure::once(Box::new(move || {
+        let bytes = js_sys::Uint8Array::new(&reader_copy.result().unwrap()).to_vec();
+        let universe = universe.borrow();
+        let options = ImportOptions {
+            threshold: selected_threshold(),
+            fit: Some((universe.width(), universe.height())),
+            ..ImportOptions::default()
+        };
+        match import::read_file(&name, &bytes, &options) {
+            Ok(pattern) => {
+                let mut hover = hover.borrow_mut();
+                reset_cells(&context, &universe, &hover.painted_cells);
+                *hover = Hover::default();
+                hover.pattern = Some(pattern.universe);
+                paint_hover(&context, &universe, &mut hover, position);
+                show_status(&format!("Click to place {}", name));
+            }
+            Err(error) => show_status(&format!("{}: {}", name, error)),
+        }
+    }) as Box<dyn FnOnce()>);
+    reader.set_onload(Some(load_handler.as_ref().unchecked_ref()));
+    load_handler.forget();
+
+    if reader.read_as_array_buffer(file).is_err() {
+        show_status(&format!("Couldn't read {}", file.name()));
+    }
+}
+
+/// Restores the board from the location hash, or the last session when
+/// there is none, falling back to the default board.
+fn initial_universe() -> Universe {
+    let hash = window().location().hash().unwrap_or_default();
+    match storage::restore_autosave(&hash) {
+        Ok(Some(saved)) => {
+            speed_input().set_value(&saved.speed.to_string());
+            return saved.snapshot.universe;
+        }
+        Ok(None) => {}
+        Err(error) => {
+            web_sys::console::warn_1(&format!("Ignoring the last session: {}", error).into());
+        }
+    }
+    if hash.len() <= 1 {
+        return Universe::new();
+    }
+
+    match SharedBoard::from_hash(&hash) {
+        Ok(shared) => {
+            if let Some(rule) = shared.pattern.rule.as_ref() {
+                if rule != rle::DEFAULT_RULE {
+                    web_sys::console::warn_1(
+                        &format!("Rule {} isn't supported, running B3/S23", rule).into(),
+                    );
+                }
+            }
+            speed_input().set_value(&shared.speed.to_string());
+            shared.pattern.universe
+        }
+        Err(error) => {
+            web_sys::console::warn_1(&format!("Ignoring the location hash: {}", error).into());
+            Universe::new()
+        }
+    }
+}
+
+/// Writes the board and speed to the location hash without adding a history
+/// entry, so the address bar always holds a shareable link, and autosaves
+/// them along with that hash.
+fn save_session(universe: &Universe) {
+    let shared = SharedBoard {
+        pattern: Pattern::from(universe.clone()),
+        speed: selected_speed(),
+    };
+    let mut hash = shared.to_hash();
+    if hash.len() > share::MAX_HASH_LENGTH {
+        web_sys::console::warn_1(&"Board is too large to share in the address bar".into());
+        hash.clear();
+    }
+
+    // Dropping the hash entirely keeps a stale link from outliving the board
+    let location = window().location();
+    let url = match hash.as_str() {
+        "" => format!(
+            "{}{}",
+            location.pathname().unwrap_or_default(),
+            location.search().unwrap_or_default()
+        ),
+        hash => format!("#{}", hash),
+    };
+    let history = window().history().unwrap();
+    if history
+        .replace_state_with_url(&JsValue::NULL, "", Some(&url))
+        .is_err()
+    {
+        web_sys::console::warn_1(&"Couldn't update the location hash".into());
+    }
+
+    if let Err(error) = storage::autosave(&saved_board(universe), &hash) {
+        show_status(&format!("Couldn't autosave: {}", error));
+    }
+}
+
+fn saved_board(universe: &Universe) -> SavedBoard {
+    SavedBoard {
+        snapshot: Snapshot::new(universe.clone()),
+        speed: selected_speed(),
+    }
+}
+
+/// Sizes the canvas to fit `universe`.
+fn fit_canvas(universe: &Universe) {
+    let canvas = element_by_id("game-of-life-canvas")
+        .dyn_into::<web_sys::HtmlCanvasElement>()
+        .unwrap();
+    canvas.set_height((CELL_SIZE + 1) * universe.height() + 1);
+    canvas.set_width((CELL_SIZE + 1) * universe.width() + 1);
+
+    let graph = element_by_id("population-graph")
+        .dyn_into::<web_sys::HtmlCanvasElement>()
+        .unwrap();
+    graph.set_width(canvas.width());
+}
+
+fn speed_input() -> web_sys::HtmlInputElement {
+    element_by_id("speed")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .unwrap()
+}
+
+/// Generations to advance each frame.
+fn selected_speed() -> u32 {
+    speed_input().value().parse().unwrap_or(1)
+}
+
+fn add_speed_handler(universe: Rc<RefCell<Universe>>) -> Result<(), JsValue> {
+    let speed_handler = Closure::wrap(Box::new(move || {
+        save_session(&universe.borrow());
+    }) as Box<dyn FnMut()>);
+    speed_input()
+        .add_event_listener_with_callback("change", speed_handler.as_ref().unchecked_ref())?;
+    speed_handler.forget();
+    Ok(())
+}
+
+fn selected_threshold() -> u8 {
+    let input = element_by_id("image-threshold")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .unwrap();
+    input.value().parse().unwrap_or(128)
+}
+
+fn selected_stamp_mode() -> StampMode {
+    let select = element_by_id("stamp-mode")
+        .dyn_into::<web_sys::HtmlSelectElement>()
+        .unwrap();
+    StampMode::from_name(&select.value()).unwrap_or(StampMode::Or)
+}
+
+fn show_status(message: &str) {
+    element_by_id("status").set_text_content(Some(message));
+}
+
+fn add_button_handler<F>(id: &str, handler: F)
+where
+    F: FnMut() + 'static,
+{
+    let button = element_by_id(id)
+        .dyn_into::<web_sys::HtmlButtonElement>()
+        .unwrap();
+    let handler = Closure::wrap(Box::new(handler) as Box<dyn FnMut()>);
+    button.set_onclick(Some(handler.as_ref().unchecked_ref()));
+    handler.forget();
+}
+
+type RegionEdit = fn(&mut Universe, Region);
+
+fn add_selection_handlers(
+    canvas: web_sys::HtmlCanvasElement,
+    context: Rc<web_sys::CanvasRenderingContext2d>,
+    universe: Rc<RefCell<Universe>>,
+    hover: Rc<RefCell<Hover>>,
+    selection: Rc<RefCell<Option<Region>>>,
+    history: Rc<RefCell<PopulationHistory>>,
+    animation_id: Rc<RefCell<i32>>,
+) -> Result<(), JsValue> {
+    // Corner the selection was started from while shift-dragging
+    let anchor: Rc<RefCell<Option<(u32, u32)>>> = Rc::new(RefCell::new(None));
+    let clipboard: Rc<RefCell<Option<Universe>>> = Rc::new(RefCell::new(None));
+
+    {
+        let canvas_copy = canvas.clone();
+        let context = Rc::clone(&context);
+        let universe = Rc::clone(&universe);
+        let selection = Rc::clone(&selection);
+        let anchor = Rc::clone(&anchor);
+
+        let mouse_down_handler = Closure::wrap(Box::new(move |event: web_sys::MouseEvent| {
+            if !event.shift_key() {
+                return;
+            }
+            let cell = event_cell(&canvas_copy, &event, &universe.borrow());
+            *anchor.borrow_mut() = Some(cell);
+            *selection.borrow_mut() = Some(Region::spanning(cell, cell));
+            draw_cells(&context, &universe.borrow());
+            draw_selection(&context, *selection.borrow());
+        }) as Box<dyn FnMut(_)>);
+        canvas.add_event_listener_with_callback(
+            "mousedown",
+            mouse_down_handler.as_ref().unchecked_ref(),
+        )?;
+        mouse_down_handler.forget();
+    }
+
+    {
+        let canvas_copy = canvas.clone();
+        let context = Rc::clone(&context);
+        let universe = Rc::clone(&universe);
+        let selection = Rc::clone(&selection);
+        let anchor = Rc::clone(&anchor);
+
+        let mouse_move_handler = Closure::wrap(Box::new(move |event: web_sys::MouseEvent| {
+            let start = match *anchor.borrow() {
+                Some(start) => start,
+                None => return,
+            };
+            let cell = event_cell(&canvas_copy, &event, &universe.borrow());
+            *selection.borrow_mut() = Some(Region::spanning(start, cell));
+            draw_cells(&context, &universe.borrow());
+            draw_selection(&context, *selection.borrow());
+        }) as Box<dyn FnMut(_)>);
+        canvas.add_event_listener_with_callback(
+            "mousemove",
+            mouse_move_handler.as_ref().unchecked_ref(),
+        )?;
+        mouse_move_handler.forget();
+    }
+
+    {
+        let anchor = Rc::clone(&anchor);
+        let mouse_up_handler = Closure::wrap(Box::new(move || {
+            *anchor.borrow_mut() = None;
+        }) as Box<dyn FnMut()>);
+        window().add_event_listener_with_callback(
+            "mouseup",
+            mouse_up_handler.as_ref().unchecked_ref(),
+        )?;
+        mouse_up_handler.forget();
+    }
+
+    // Buttons that edit the cells inside the selection
+    let edits: [(&str, RegionEdit); 4] = [
+        ("selection-clear", |universe, region| {
+            universe.fill_region(region, cell::Cell::Dead)
+        }),
+        ("selection-fill", |universe, region| {
+            universe.fill_region(region, cell::Cell::Alive)
+        }),
+        ("selection-invert", |universe, region| {
+            universe.invert_region(region)
+        }),
+        ("selection-randomize", |universe, region| {
+            universe.randomize_region(region, || js_sys::Math::random() < 0.5)
+        }),
+    ];
+    for (id, edit) in edits.iter().cloned() {
+        let context = Rc::clone(&context);
+        let universe = Rc::clone(&universe);
+        let selection = Rc::clone(&selection);
+        let history = Rc::clone(&history);
+        add_button_handler(id, move || {
+            if let Some(region) = *selection.borrow() {
+                edit(&mut universe.borrow_mut(), region);
+                draw_cells(&context, &universe.borrow());
+                draw_selection(&context, Some(region));
+                mark_population(&history, &universe.borrow(), MarkerKind::Edit);
+                save_session(&universe.borrow());
+            }
+        });
+    }
+
+    {
+        let universe = Rc::clone(&universe);
+        let selection = Rc::clone(&selection);
+        add_button_handler("selection-identify", move || {
+            if let Some(region) = *selection.borrow() {
+                let pattern = universe.borrow().copy_region(region);
+                match apgcode::encode(&pattern) {
+                    Some(code) => show_status(&format!("Selection is {}", code)),
+                    None => show_status("Selection does not settle into a known object"),
+                }
+            }
+        });
+    }
+
+    {
+        let universe = Rc::clone(&universe);
+        let selection = Rc::clone(&selection);
+        let clipboard = Rc::clone(&clipboard);
+        add_button_handler("selection-copy", move || {
+            let pattern = copied_pattern(&universe.borrow(), *selection.borrow());
+            write_clipboard(&pattern);
+            *clipboard.borrow_mut() = Some(pattern);
+        });
+    }
+
+    {
+        let context = Rc::clone(&context);
+        let universe = Rc::clone(&universe);
+        let selection = Rc::clone(&selection);
+        let clipboard = Rc::clone(&clipboard);
+        let history = Rc::clone(&history);
+        add_button_handler("selection-cut", move || {
+            if let Some(region) = *selection.borrow() {
+                let mut universe = universe.borrow_mut();
+                let pattern = universe.copy_region(region);
+                write_clipboard(&pattern);
+                *clipboard.borrow_mut() = Some(pattern);
+                universe.fill_region(region, cell::Cell::Dead);
+                draw_cells(&context, &universe);
+                draw_selection(&context, Some(region));
+                mark_population(&history, &universe, MarkerKind::Edit);
+                save_session(&universe);
+            }
+        });
+    }
+
+    // Looks for a parent of the selection over the next frames. Starting a
+    // new search stops the last one.
+    {
+        let cancel = Rc::new(RefCell::new(Arc::new(AtomicBool::new(false))));
+
+        {
+            let universe = Rc::clone(&universe);
+            let selection = Rc::clone(&selection);
+            let hover = Rc::clone(&hover);
+            let animation_id = Rc::clone(&animation_id);
+            let cancel = Rc::clone(&cancel);
+            add_button_handler("selection-predecessor", move || {
+                let region = match *selection.borrow() {
+                    Some(region) => region,
+                    None => {
+                        show_status("Select a pattern to find a parent for");
+                        return;
+                    }
+                };
+                pause(&animation_id);
+                cancel.borrow().store(true, Ordering::Relaxed);
+                *cancel.borrow_mut() = Arc::new(AtomicBool::new(false));
+
+                let options = PredecessorOptions {
+                    time_limit: Some(PREDECESSOR_TIME_LIMIT),
+                    cancel: Some(Arc::clone(&cancel.borrow())),
+                    ..PredecessorOptions::default()
+                };
+                let target = universe.borrow().copy_region(region);
+                run_predecessor_search(PredecessorSearch::new(&target, options), Rc::clone(&hover));
+            });
+        }
+
+        add_button_handler("predecessor-cancel", move || {
+            cancel.borrow().store(true, Ordering::Relaxed);
+        });
+    }
+
+    // Finds the chosen library pattern, or the last one copied, and tints
+    // every match until the board is next redrawn
+    {
+        let list = element_by_id("find-pattern");
+        for pattern in library::PATTERNS.iter() {
+            let option = utils::document().create_element("option")?;
+            option.set_attribute("value", pattern.key)?;
+            option.set_text_content(Some(pattern.name));
+            list.append_child(&option)?;
+        }
+
+        let context = Rc::clone(&context);
+        let universe = Rc::clone(&universe);
+        let clipboard = Rc::clone(&clipboard);
+        let animation_id = Rc::clone(&animation_id);
+        add_button_handler("find", move || {
+            let pattern = match library::get(&selected_find_pattern()) {
+                Some(pattern) => pattern.universe(),
+                None => match clipboard.borrow().as_ref() {
+                    Some(pattern) => pattern.clone(),
+                    None => {
+                        show_status("Copy a pattern to find first");
+                        return;
+                    }
+                },
+            };
+            let options = SearchOptions {
+                padding: if find_isolated() { census::PADDING } else { 0 },
+            };
+
+            pause(&animation_id);
+            let universe = universe.borrow();
+            let matches = universe.search(&pattern, &options);
+            draw_cells(&context, &universe);
+            draw_matches(&context, &universe, &matches);
+            show_status(&describe_matches(&matches));
+        });
+    }
+
+    // Pasting hands the clipboard to the same hover path prefab drops use.
+    // The system clipboard may hold RLE from another program; when the
+    // browser won't share it, the last pattern copied here is used instead.
+    {
+        let hover = Rc::clone(&hover);
+        let animation_id = Rc::clone(&animation_id);
+        let clipboard = Rc::clone(&clipboard);
+        add_button_handler("selection-paste", move || {
+            let read = window().navigator().clipboard().read_text();
+
+            let hover_copy = Rc::clone(&hover);
+            let animation_id_copy = Rc::clone(&animation_id);
+            let on_text = Closure::once(Box::new(move |text: JsValue| {
+                let text = text.as_string().unwrap_or_default();
+                hover_pasted_text(&text, &hover_copy, &animation_id_copy);
+            }) as Box<dyn FnOnce(JsValue)>);
+
+            let hover = Rc::clone(&hover);
+            let animation_id = Rc::clone(&animation_id);
+            let clipboard = Rc::clone(&clipboard);
+            let on_error = Closure::once(Box::new(move |_: JsValue| {
+                if let Some(pattern) = clipboard.borrow().as_ref() {
+                    pause(&animation_id);
+                    hover.borrow_mut().pattern = Some(pattern.clone());
+                }
+            }) as Box<dyn FnOnce(JsValue)>);
+
+            let _ = read.then(&on_text).catch(&on_error);
+            on_text.forget();
+            on_error.forget();
+        });
+    }
+
+    // Ctrl+C and Ctrl+V, unless a form control has focus
+    {
+        let universe = Rc::clone(&universe);
+        let selection = Rc::clone(&selection);
+        let copy_handler = Closure::wrap(Box::new(move |event: web_sys::ClipboardEvent| {
+            if form_control_focused() {
+                return;
+            }
+            let pattern = copied_pattern(&universe.borrow(), *selection.borrow());
+            if let Some(data) = event.clipboard_data() {
+                let rle = pattern.to_rle();
+                if data.set_data("text/plain", &rle).is_ok() {
+                    event.prevent_default();
+                }
+            }
+        }) as Box<dyn FnMut(_)>);
+        utils::document()
+            .add_event_listener_with_callback("copy", copy_handler.as_ref().unchecked_ref())?;
+        copy_handler.forget();
+    }
+
+    {
+        let paste_handler = Closure::wrap(Box::new(move |event: web_sys::ClipboardEvent| {
+            if form_control_focused() {
+                return;
+            }
+            if let Some(text) = event
+                .clipboard_data()
+                .and_then(|data| data.get_data("text/plain").ok())
+            {
+                event.prevent_default();
+                hover_pasted_text(&text, &hover, &animation_id);
+            }
+        }) as Box<dyn FnMut(_)>);
+        utils::document()
+            .add_event_listener_with_callback("paste", paste_handler.as_ref().unchecked_ref())?;
+        paste_handler.forget();
+    }
+
+    Ok(())
+}
+
+/// The selected cells, or the whole board cropped to its live cells.
+fn copied_pattern(universe: &Universe, selection: Option<Region>) -> Universe {
+    match selection {
+        Some(region) => universe.copy_region(region),
+        None => universe.crop(),
+    }
+}
+
+/// Runs `search` a slice per animation frame, reporting progress in the
+/// status area, and floats the parent it finds over the board to be placed.
+fn run_predecessor_search(search: PredecessorSearch, hover: Rc<RefCell<Hover>>) {
+    let frame = Rc::new(RefCell::new(None));
+    let next_frame = Rc::clone(&frame);
+    let mut search = search;
+
+    *frame.borrow_mut() =
+        Some(Closure::wrap(
+            Box::new(move || match search.run(PREDECESSOR_NODES_PER_FRAME) {
+                Progress::Searching => {
+                    show_status(&format!(
+                        "Searching for a parent, {} cells tried…",
+                        search.nodes()
+                    ));
+                    request_animation_frame(next_frame.borrow().as_ref().unwrap());
+                }
+                Progress::Done(Ok(parent)) => {
+                    hover.borrow_mut().pattern = Some(parent);
+                    show_status("Click to place the parent");
+                }
+                Progress::Done(Err(error)) => show_status(&format!("Parent search: {}", error)),
+            }) as Box<dyn FnMut()>,
+        ));
+    request_animation_frame(frame.borrow().as_ref().unwrap());
+}
+
+fn selected_find_pattern() -> String {
+    element_by_id("find-pattern")
+        .dyn_into::<web_sys::HtmlSelectElement>()
+        .unwrap()
+        .value()
+}
+
+fn find_isolated() -> bool {
+    element_by_id("find-isolated")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .unwrap()
+        .checked()
+}
+
+// Search results for the status area, listing the first few
+fn describe_matches(matches: &[Match]) -> String {
+    const LISTED: usize = 5;
+    let mut description = match matches.len() {
+        0 => return String::from("No matches"),
+        1 => String::from("1 match"),
+        count => format!("{} matches", count),
+    };
+    let listed: Vec<String> = matches
+        .iter()
+        .take(LISTED)
+        .map(|found| {
+            format!(
+                "({}, {}) {}",
+                found.region.row,
+                found.region.column,
+                found.orientation.name()
+            )
+        })
+        .collect();
+    description.push_str(": ");
+    description.push_str(&listed.join(", "));
+    if matches.len() > LISTED {
+        description.push_str(", …");
+    }
+    description
+}
+
+/// Puts `pattern` on the system clipboard as RLE.
+fn write_clipboard(pattern: &Universe) {
+    let write = window()
+        .navigator()
+        .clipboard()
+        .write_text(&pattern.to_rle());
+    let on_error = Closure::once(Box::new(move |_: JsValue| {
+        show_status("Couldn't copy to the system clipboard");
+    }) as Box<dyn FnOnce(JsValue)>);
+    let _ = write.catch(&on_error);
+    on_error.forget();
+}
+
+/// Parses pasted text and floats it over the board, ready to be placed.
+fn hover_pasted_text(text: &str, hover: &RefCell<Hover>, animation_id: &RefCell<i32>) {
+    match formats::parse_any(text) {
+        Ok(pattern) => {
+            pause(animation_id);
+            hover.borrow_mut().pattern = Some(pattern.universe.crop());
+            show_status("Click to place the pasted pattern");
+        }
+        Err(error) => show_status(&format!("Clipboard isn't a pattern: {}", error)),
+    }
+}
+
+fn form_control_focused() -> bool {
+    utils::document()
+        .active_element()
+        .map(|element| ["INPUT", "SELECT", "TEXTAREA"].contains(&element.tag_name().as_str()))
+        .unwrap_or(false)
+}
+
+/// Save slot controls: a list of slots, a name box, and buttons to save,
+/// load, rename and delete.
+fn add_slot_handlers(
+    context: Rc<web_sys::CanvasRenderingContext2d>,
+    universe: Rc<RefCell<Universe>>,
+    history: Rc<RefCell<PopulationHistory>>,
+) -> Result<(), JsValue> {
+    refresh_slots(None);
+
+    {
+        let universe = Rc::clone(&universe);
+        add_button_handler("slot-save", move || {
+            let name = slot_name_input().value();
+            let name = name.trim();
+            match storage::save(name, &saved_board(&universe.borrow())) {
+                Ok(()) => {
+                    refresh_slots(Some(name));
+                    show_status(&format!("Saved \"{}\"", name));
+                }
+                Err(error) => show_status(&format!("Couldn't save: {}", error)),
+            }
+        });
+    }
+
+    {
+        let universe = Rc::clone(&universe);
+        add_button_handler("slot-load", move || {
+            let name = match selected_slot() {
+                Some(name) => name,
+                None => return,
+            };
+            match storage::load(&name) {
+                Ok(saved) => {
+                    speed_input().set_value(&saved.speed.to_string());
+                    *universe.borrow_mut() = saved.snapshot.universe;
+                    let universe = universe.borrow();
+                    fit_canvas(&universe);
+                    draw_grid(&context, &universe);
+                    draw_cells(&context, &universe);
+                    mark_population(&history, &universe, MarkerKind::Edit);
+                    save_session(&universe);
+                    show_status(&format!("Loaded \"{}\"", name));
+                }
+                Err(error) => show_status(&format!("Couldn't load: {}", error)),
+            }
+        });
+    }
+
+    add_button_handler("slot-rename", move || {
+        let (from, to) = match selected_slot() {
+            Some(from) => (from, slot_name_input().value()),
+            None => return,
+        };
+        match storage::rename(&from, to.trim()) {
+            Ok(()) => refresh_slots(Some(to.trim())),
+            Err(error) => show_status(&format!("Couldn't rename: {}", error)),
+        }
+    });
+
+    add_button_handler("slot-delete", move || {
+        if let Some(name) = selected_slot() {
+            match storage::delete(&name) {
+                Ok(()) => refresh_slots(None),
+                Err(error) => show_status(&format!("Couldn't delete: {}", error)),
+            }
+        }
+    });
+
+    // Catches the generations run since the last edit
+    {
+        let autosave_handler = Closure::wrap(Box::new(move || {
+            save_session(&universe.borrow());
+        }) as Box<dyn FnMut()>);
+        window().add_event_listener_with_callback(
+            "pagehide",
+            autosave_handler.as_ref().unchecked_ref(),
+        )?;
+        autosave_handler.forget();
+    }
+
+    Ok(())
+}
+
+fn slot_list() -> web_sys::HtmlSelectElement {
+    element_by_id("save-slots")
+        .dyn_into::<web_sys::HtmlSelectElement>()
+        .unwrap()
+}
+
+fn slot_name_input() -> web_sys::HtmlInputElement {
+    element_by_id("slot-name")
+        .dyn_into::<web_sys::HtmlInputElement>()
+        .unwrap()
+}
+
+fn selected_slot() -> Option<String> {
+    Some(slot_list().value()).filter(|name| !name.is_empty())
+}
+
+/// Refills the slot list from storage, selecting `selected` if given.
+fn refresh_slots(selected: Option<&str>) {
+    let list = slot_list();
+    list.set_inner_html("");
+    let names = match storage::slot_names() {
+        Ok(names) => names,
+        Err(error) => {
+            show_status(&format!("Couldn't list saves: {}", error));
+            return;
+        }
+    };
+    let document = utils::document();
+    for name in names.iter() {
+        let option = document.create_element("option").unwrap();
+        option.set_attribute("value", name).unwrap();
+        option.set_text_content(Some(name));
+        list.append_child(&option).unwrap();
+    }
+    if let Some(selected) = selected {
+        list.set_value(selected);
+    }
+}
+
 fn add_clear_handler(
     context: Rc<web_sys::CanvasRenderingContext2d>,
     universe: Rc<RefCell<Universe>>,
+    history: Rc<RefCell<PopulationHistory>>,
 ) {
     let button = element_by_id("clear")
         .dyn_into::<web_sys::HtmlButtonElement>()
         .unwrap();
     let clear_handler = Closure::wrap(Box::new(move || {
         universe.borrow_mut().clear();
-        draw_cells(&context, &*universe.borrow());
+        draw_cells(&context, &universe.borrow());
+        mark_population(&history, &universe.borrow(), MarkerKind::Edit);
+        save_session(&universe.borrow());
     }) as Box<dyn FnMut()>);
 
     button.set_onclick(Some(clear_handler.as_ref().unchecked_ref()));
@@ -305,8 +1478,7 @@ fn draw_grid(context: &web_sys::CanvasRenderingContext2d, universe: &Universe) {
 
     context.begin_path();
 
-    let grid_syle = JsValue::from(String::from(GRID_COLOR));
-    context.set_stroke_style(&grid_syle);
+    context.set_stroke_style_str(GRID_COLOR);
 
     for i in 0..=width {
         let x = i * (CELL_SIZE + 1) + 1;
@@ -330,8 +1502,7 @@ fn draw_cells(context: &web_sys::CanvasRenderingContext2d, universe: &Universe)
 
     context.begin_path();
 
-    let alive_style = JsValue::from(String::from(ALIVE_COLOR));
-    context.set_fill_style(&alive_style);
+    context.set_fill_style_str(ALIVE_COLOR);
     for row in 0..height {
         for col in 0..width {
             let idx = universe.get_index(row, col);
@@ -348,8 +1519,7 @@ fn draw_cells(context: &web_sys::CanvasRenderingContext2d, universe: &Universe)
         }
     }
 
-    let dead_style = JsValue::from(String::from(DEAD_COLOR));
-    context.set_fill_style(&dead_style);
+    context.set_fill_style_str(DEAD_COLOR);
     for row in 0..height {
         for col in 0..width {
             let idx = universe.get_index(row, col);
@@ -369,20 +1539,131 @@ fn draw_cells(context: &web_sys::CanvasRenderingContext2d, universe: &Universe)
     context.stroke();
 }
 
-// TODO: this is very similar to draw_cells, maybe refactor into one?
-fn reset_cells(
+// Tints the selected cells. Drawn over `draw_cells`, which erases it again.
+fn draw_selection(context: &web_sys::CanvasRenderingContext2d, selection: Option<Region>) {
+    let region = match selection {
+        Some(region) => region,
+        None => return,
+    };
+
+    context.set_fill_style_str(SELECTION_COLOR);
+    context.fill_rect(
+        (region.column * (CELL_SIZE + 1) + 1) as f64,
+        (region.row * (CELL_SIZE + 1) + 1) as f64,
+        (region.width * (CELL_SIZE + 1) - 1) as f64,
+        (region.height * (CELL_SIZE + 1) - 1) as f64,
+    );
+}
+
+/// Marks `kind` on the population graph at the current generation and
+/// redraws it, since the render loop may be paused.
+fn mark_population(history: &RefCell<PopulationHistory>, universe: &Universe, kind: MarkerKind) {
+    history.borrow_mut().mark(universe, kind);
+    draw_population(&history.borrow());
+}
+
+/// Draws the population line chart on `#population-graph`. Generations run
+/// along the bottom from the oldest sample to the newest, and the
+/// population axis grows to fit the largest sample.
+fn draw_population(history: &PopulationHistory) {
+    let canvas = element_by_id("population-graph")
+        .dyn_into::<web_sys::HtmlCanvasElement>()
+        .unwrap();
+    let context = canvas
+        .get_context("2d")
+        .unwrap()
+        .unwrap()
+        .dyn_into::<web_sys::CanvasRenderingContext2d>()
+        .unwrap();
+    let (width, height) = (canvas.width() as f64, canvas.height() as f64);
+    context.clear_rect(0f64, 0f64, width, height);
+
+    let samples = history.samples();
+    let (first, last) = match (samples.front(), samples.back()) {
+        (Some(first), Some(last)) => (first.generation, last.generation),
+        _ => return,
+    };
+    let scale = history.scale();
+    let plot_width = width - GRAPH_LEFT_MARGIN - 1f64;
+    let plot_bottom = height - GRAPH_BOTTOM_MARGIN;
+    let x = |generation: u64| {
+        GRAPH_LEFT_MARGIN + (generation - first) as f64 / (last - first).max(1) as f64 * plot_width
+    };
+    let y = |population: u64| plot_bottom - population as f64 / scale as f64 * (plot_bottom - 1f64);
+
+    context.set_stroke_style_str(GRID_COLOR);
+    context.begin_path();
+    context.move_to(GRAPH_LEFT_MARGIN, 0f64);
+    context.line_to(GRAPH_LEFT_MARGIN, plot_bottom);
+    context.line_to(width, plot_bottom);
+    context.stroke();
+
+    context.set_fill_style_str(ALIVE_COLOR);
+    context.set_font("10px sans-serif");
+    context.set_text_align("left");
+    context.fill_text(&scale.to_string(), 2f64, 10f64).unwrap();
+    context.fill_text("0", 2f64, plot_bottom).unwrap();
+    context
+        .fill_text(&first.to_string(), GRAPH_LEFT_MARGIN, height - 2f64)
+        .unwrap();
+    context.set_text_align("right");
+    context
+        .fill_text(&last.to_string(), width - 2f64, height - 2f64)
+        .unwrap();
+
+    for marker in history.markers() {
+        context.set_stroke_style_str(match marker.kind {
+            MarkerKind::Pause => PAUSE_MARKER_COLOR,
+            MarkerKind::Edit => EDIT_MARKER_COLOR,
+        });
+        context.begin_path();
+        context.move_to(x(marker.generation), 0f64);
+        context.line_to(x(marker.generation), plot_bottom);
+        context.stroke();
+    }
+
+    context.set_stroke_style_str(ALIVE_COLOR);
+    context.begin_path();
+    for (idx, sample) in samples.iter().enumerate() {
+        if idx == 0 {
+            context.move_to(x(sample.generation), y(sample.population));
+        } else {
+            context.line_to(x(sample.generation), y(sample.population));
+        }
+    }
+    context.stroke();
+}
+
+// Tints each match, wrapping around the edges. Erased by `draw_cells`.
+fn draw_matches(
     context: &web_sys::CanvasRenderingContext2d,
     universe: &Universe,
-    cells_to_reset: &Vec<(u32, u32)>,
+    matches: &[Match],
 ) {
-    let alive_style = JsValue::from(String::from(ALIVE_COLOR));
-    context.set_fill_style(&alive_style);
-    for (row, col) in cells_to_reset.clone().iter() {
-        // TODO: needs edge logic for shapes that wrap around world on borders
-        if *row >= universe.height() || *col >= universe.width() {
-            continue;
+    context.set_fill_style_str(MATCH_COLOR);
+    for found in matches.iter() {
+        let region = found.region;
+        for row in region.row..region.row + region.height {
+            for col in region.column..region.column + region.width {
+                context.fill_rect(
+                    ((col % universe.width()) * (CELL_SIZE + 1) + 1) as f64,
+                    ((row % universe.height()) * (CELL_SIZE + 1) + 1) as f64,
+                    CELL_SIZE as f64,
+                    CELL_SIZE as f64,
+                );
+            }
         }
+    }
+}
 
+// TODO: this is very similar to draw_cells, maybe refactor into one?
+fn reset_cells(
+    context: &web_sys::CanvasRenderingContext2d,
+    universe: &Universe,
+    cells_to_reset: &[(u32, u32)],
+) {
+    context.set_fill_style_str(ALIVE_COLOR);
+    for (row, col) in cells_to_reset.iter() {
         let idx = universe.get_index(*row, *col);
         if universe.cells()[idx] != cell::Cell::Alive {
             continue;
@@ -396,13 +1677,8 @@ fn reset_cells(
         );
     }
 
-    let dead_style = JsValue::from(String::from(DEAD_COLOR));
-    context.set_fill_style(&dead_style);
-    for (row, col) in cells_to_reset.clone().iter() {
-        // TODO: needs edge logic for shapes that wrap around world on borders
-        if *row >= universe.height() || *col >= universe.width() {
-            continue;
-        }
+    context.set_fill_style_str(DEAD_COLOR);
+    for (row, col) in cells_to_reset.iter() {
         let idx = universe.get_index(*row, *col);
         if universe.cells()[idx] != cell::Cell::Dead {
             continue;
@@ -0,0 +1,95 @@
+//! The built-in pattern library shown in the page's pattern palette.
+//!
+//! Each pattern is an RLE file under `src/patterns`, embedded at compile time
+//! and described here with the metadata the palette needs.
+
+use crate::formats::rle;
+use crate::universe::Universe;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Category {
+    StillLife,
+    Oscillator,
+    Spaceship,
+    Gun,
+    Methuselah,
+}
+
+impl Category {
+    pub fn name(self) -> &'static str {
+        match self {
+            Category::StillLife => "Still lifes",
+            Category::Oscillator => "Oscillators",
+            Category::Spaceship => "Spaceships",
+            Category::Gun => "Guns",
+            Category::Methuselah => "Methuselahs",
+        }
+    }
+}
+
+/// Categories in the order the palette lists them.
+pub const CATEGORIES: &[Category] = &[
+    Category::StillLife,
+    Category::Oscillator,
+    Category::Spaceship,
+    Category::Gun,
+    Category::Methuselah,
+];
+
+#[derive(Debug)]
+pub struct LibraryPattern {
+    /// Stable identifier, used as the drag-and-drop payload.
+    pub key: &'static str,
+    pub name: &'static str,
+    pub category: Category,
+    /// Generations until the pattern repeats, `None` for patterns that
+    /// never do.
+    pub period: Option<u32>,
+    pub rule: &'static str,
+    /// The embedded RLE file.
+    pub source: &'static str,
+}
+
+impl LibraryPattern {
+    pub fn universe(&self) -> Universe {
+        // Embedded files are checked by the library tests, so this can't fail
+        Universe::from_rle(self.source)
+            .unwrap_or_else(|error| panic!("Pattern `{}` is malformed: {}", self.key, error))
+    }
+}
+
+macro_rules! pattern {
+    ($key:literal, $name:literal, $category:ident, $period:expr) => {
+        LibraryPattern {
+            key: $key,
+            name: $name,
+            category: Category::$category,
+            period: $period,
+            rule: rle::DEFAULT_RULE,
+            source: include_str!(concat!("patterns/", $key, ".rle")),
+        }
+    };
+}
+
+pub const PATTERNS: &[LibraryPattern] = &[
+    pattern!("block", "Block", StillLife, Some(1)),
+    pattern!("beehive", "Beehive", StillLife, Some(1)),
+    pattern!("blinker", "Blinker", Oscillator, Some(2)),
+    pattern!("toad", "Toad", Oscillator, Some(2)),
+    pattern!("pulsar", "Pulsar", Oscillator, Some(3)),
+    pattern!("glider", "Glider", Spaceship, Some(4)),
+    pattern!("lwss", "Lightweight spaceship", Spaceship, Some(4)),
+    pattern!("gosper-glider-gun", "Gosper glider gun", Gun, Some(30)),
+    pattern!("r-pentomino", "R-pentomino", Methuselah, None),
+    pattern!("acorn", "Acorn", Methuselah, None),
+];
+
+pub fn get(key: &str) -> Option<&'static LibraryPattern> {
+    PATTERNS.iter().find(|pattern| pattern.key == key)
+}
+
+pub fn in_category(category: Category) -> impl Iterator<Item = &'static LibraryPattern> {
+    PATTERNS
+        .iter()
+        .filter(move |pattern| pattern.category == category)
+}
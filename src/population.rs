@@ -0,0 +1,166 @@
+//! Population over time, for the graph under the board.
+//!
+//! Samples are kept in a ring buffer of the most recent generations, along
+//! with markers for events such as pauses and edits that explain sudden
+//! changes in the line.
+
+use std::collections::VecDeque;
+use std::fmt::Write;
+
+use crate::universe::Universe;
+
+/// Generations kept by `PopulationHistory::default`.
+pub const DEFAULT_CAPACITY: usize = 1000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Sample {
+    pub generation: u64,
+    pub population: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MarkerKind {
+    Pause,
+    Edit,
+}
+
+impl MarkerKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            MarkerKind::Pause => "pause",
+            MarkerKind::Edit => "edit",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Marker {
+    pub generation: u64,
+    pub kind: MarkerKind,
+}
+
+#[derive(Clone, Debug)]
+pub struct PopulationHistory {
+    capacity: usize,
+    samples: VecDeque<Sample>,
+    markers: VecDeque<Marker>,
+}
+
+impl PopulationHistory {
+    /// A history keeping the last `capacity` generations.
+    pub fn new(capacity: usize) -> PopulationHistory {
+        PopulationHistory {
+            capacity: capacity.max(1),
+            samples: VecDeque::new(),
+            markers: VecDeque::new(),
+        }
+    }
+
+    /// Samples, oldest first.
+    pub fn samples(&self) -> &VecDeque<Sample> {
+        &self.samples
+    }
+
+    /// Markers still inside the sampled generations, oldest first.
+    pub fn markers(&self) -> &VecDeque<Marker> {
+        &self.markers
+    }
+
+    pub fn clear(&mut self) {
+        self.samples.clear();
+        self.markers.clear();
+    }
+
+    /// Records the population of `universe` at its current generation.
+    /// Recording the same generation again replaces its sample, and going
+    /// back to an earlier generation, as loading a save can, starts over.
+    pub fn record(&mut self, universe: &Universe) {
+        let sample = Sample {
+            generation: universe.generation(),
+            population: universe.population(),
+        };
+        match self.samples.back_mut() {
+            Some(last) if last.generation == sample.generation => {
+                *last = sample;
+                return;
+            }
+            Some(last) if last.generation > sample.generation => self.clear(),
+            _ => {}
+        }
+
+        self.samples.push_back(sample);
+        if self.samples.len() > self.capacity {
+            self.samples.pop_front();
+            let oldest = self.samples.front().map_or(0, |sample| sample.generation);
+            while self
+                .markers
+                .front()
+                .is_some_and(|marker| marker.generation < oldest)
+            {
+                self.markers.pop_front();
+            }
+        }
+    }
+
+    /// Records `universe` and marks its generation with `kind`.
+    pub fn mark(&mut self, universe: &Universe, kind: MarkerKind) {
+        self.record(universe);
+        let marker = Marker {
+            generation: universe.generation(),
+            kind,
+        };
+        if !self.markers.contains(&marker) {
+            self.markers.push_back(marker);
+        }
+    }
+
+    /// Top of the graph's population axis: the largest sample rounded up to
+    /// 1, 2 or 5 times a power of ten.
+    pub fn scale(&self) -> u64 {
+        let max = self
+            .samples
+            .iter()
+            .map(|sample| sample.population)
+            .max()
+            .unwrap_or(0);
+        let mut step = 1;
+        loop {
+            for &multiple in [1, 2, 5].iter() {
+                if max <= step * multiple {
+                    return step * multiple;
+                }
+            }
+            step *= 10;
+        }
+    }
+
+    /// The samples as CSV with `generation`, `population` and `events`
+    /// columns. Events are the names of the markers on that generation,
+    /// separated by `;`.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("generation,population,events\n");
+        for sample in self.samples.iter() {
+            let events: Vec<&str> = self
+                .markers
+                .iter()
+                .filter(|marker| marker.generation == sample.generation)
+                .map(|marker| marker.kind.name())
+                .collect();
+            writeln!(
+                csv,
+                "{},{},{}",
+                sample.generation,
+                sample.population,
+                events.join(";")
+            )
+            .unwrap();
+        }
+        csv
+    }
+}
+
+impl Default for PopulationHistory {
+    fn default() -> PopulationHistory {
+        PopulationHistory::new(DEFAULT_CAPACITY)
+    }
+}
@@ -0,0 +1,305 @@
+//! Searching for a parent: a board that becomes a given pattern after one
+//! generation. A pattern with no parent at all is a Garden of Eden.
+//!
+//! The search assigns the parent's cells one at a time in row-major order,
+//! dead before alive, and backtracks as soon as some target cell can no
+//! longer come out right. With forward checking, a target cell is checked
+//! whenever any cell around it is assigned, counting the cells still
+//! unassigned as either state; without it, only once all nine are known.
+//!
+//! Searches can take exponentially long, so they run in steps that can be
+//! spread over animation frames, and stop at a time limit or when
+//! cancelled.
+
+use std::error::Error;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::cell::Cell;
+use crate::universe::Universe;
+
+/// Nodes between checks of the clock and the cancel flag.
+const CHECK_INTERVAL: u64 = 1024;
+
+#[derive(Clone, Debug)]
+pub struct PredecessorOptions {
+    /// Search a parent of the same size that wraps around its edges, as the
+    /// board does. Otherwise the parent is a patch one cell larger on every
+    /// side, and only the target's own cells are constrained.
+    pub torus: bool,
+    /// Check target cells before their whole neighborhood is assigned.
+    pub forward_checking: bool,
+    pub time_limit: Option<Duration>,
+    /// Set to stop the search early, for example from another thread.
+    pub cancel: Option<Arc<AtomicBool>>,
+}
+
+impl Default for PredecessorOptions {
+    fn default() -> PredecessorOptions {
+        PredecessorOptions {
+            torus: false,
+            forward_checking: true,
+            time_limit: None,
+            cancel: None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PredecessorError {
+    /// Every candidate parent was ruled out.
+    GardenOfEden,
+    TimedOut,
+    Cancelled,
+}
+
+impl fmt::Display for PredecessorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PredecessorError::GardenOfEden => write!(f, "no parent exists, it's a Garden of Eden"),
+            PredecessorError::TimedOut => write!(f, "the search ran out of time"),
+            PredecessorError::Cancelled => write!(f, "the search was cancelled"),
+        }
+    }
+}
+
+impl Error for PredecessorError {}
+
+/// How far a search has got.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Progress {
+    Searching,
+    Done(Result<Universe, PredecessorError>),
+}
+
+// A target cell and the parent cells it depends on
+struct Constraint {
+    alive: bool,
+    // Parent variable of the cell itself
+    center: usize,
+    // Parent variables of the eight neighbors
+    neighbors: [usize; 8],
+    // The neighborhood variable assigned last
+    last: usize,
+}
+
+pub struct PredecessorSearch {
+    options: PredecessorOptions,
+    width: u32,
+    height: u32,
+    constraints: Vec<Constraint>,
+    // Constraints each parent variable takes part in
+    watchers: Vec<Vec<usize>>,
+    values: Vec<Option<bool>>,
+    // Next variable to assign
+    position: usize,
+    nodes: u64,
+    clock: Clock,
+    done: Option<Result<Universe, PredecessorError>>,
+}
+
+impl PredecessorSearch {
+    pub fn new(target: &Universe, options: PredecessorOptions) -> PredecessorSearch {
+        let (width, height, margin) = if options.torus {
+            (target.width(), target.height(), 0)
+        } else {
+            (target.width() + 2, target.height() + 2, 1)
+        };
+        let variable = |row: i64, col: i64| {
+            (row.rem_euclid(height as i64) * width as i64 + col.rem_euclid(width as i64)) as usize
+        };
+
+        let mut constraints = vec![];
+        let mut watchers = vec![vec![]; (width * height) as usize];
+        for row in 0..target.height() {
+            for col in 0..target.width() {
+                let (center_row, center_col) = ((row + margin) as i64, (col + margin) as i64);
+                let mut neighbors = [0; 8];
+                let mut idx = 0;
+                for d_row in -1..=1 {
+                    for d_col in -1..=1 {
+                        if (d_row, d_col) != (0, 0) {
+                            neighbors[idx] = variable(center_row + d_row, center_col + d_col);
+                            idx += 1;
+                        }
+                    }
+                }
+                let center = variable(center_row, center_col);
+                let last = neighbors.iter().cloned().chain(Some(center)).max().unwrap();
+
+                let id = constraints.len();
+                for &var in neighbors.iter().chain(Some(&center)) {
+                    if !watchers[var].contains(&id) {
+                        watchers[var].push(id);
+                    }
+                }
+                constraints.push(Constraint {
+                    alive: target.cells()[target.get_index(row, col)] == Cell::Alive,
+                    center,
+                    neighbors,
+                    last,
+                });
+            }
+        }
+
+        PredecessorSearch {
+            options,
+            width,
+            height,
+            constraints,
+            watchers,
+            values: vec![None; (width * height) as usize],
+            position: 0,
+            nodes: 0,
+            clock: Clock::start(),
+            done: None,
+        }
+    }
+
+    /// Cells tried so far.
+    pub fn nodes(&self) -> u64 {
+        self.nodes
+    }
+
+    /// Tries up to `max_nodes` more cells. Once done, keeps returning the
+    /// same result.
+    pub fn run(&mut self, max_nodes: u64) -> Progress {
+        if let Some(result) = self.done.as_ref() {
+            return Progress::Done(result.clone());
+        }
+
+        for _ in 0..max_nodes {
+            if self.nodes.is_multiple_of(CHECK_INTERVAL) {
+                if let Some(error) = self.interruption() {
+                    return self.finish(Err(error));
+                }
+            }
+
+            if self.position == self.values.len() {
+                let parent = self.parent();
+                return self.finish(Ok(parent));
+            }
+
+            let var = self.position;
+            match self.values[var] {
+                None | Some(false) => {
+                    self.values[var] = Some(self.values[var].is_some());
+                    self.nodes += 1;
+                    if self.consistent(var) {
+                        self.position += 1;
+                    }
+                }
+                Some(true) => {
+                    self.values[var] = None;
+                    if var == 0 {
+                        return self.finish(Err(PredecessorError::GardenOfEden));
+                    }
+                    self.position -= 1;
+                }
+            }
+        }
+        Progress::Searching
+    }
+
+    fn interruption(&self) -> Option<PredecessorError> {
+        let cancelled = self
+            .options
+            .cancel
+            .as_ref()
+            .is_some_and(|cancel| cancel.load(Ordering::Relaxed));
+        if cancelled {
+            return Some(PredecessorError::Cancelled);
+        }
+        match self.options.time_limit {
+            Some(limit) if self.clock.elapsed() > limit => Some(PredecessorError::TimedOut),
+            _ => None,
+        }
+    }
+
+    fn finish(&mut self, result: Result<Universe, PredecessorError>) -> Progress {
+        self.done = Some(result.clone());
+        Progress::Done(result)
+    }
+
+    // Whether every constraint on `var` can still be met
+    fn consistent(&self, var: usize) -> bool {
+        self.watchers[var].iter().all(|&id| {
+            let constraint = &self.constraints[id];
+            if !self.options.forward_checking && constraint.last != var {
+                return true;
+            }
+            let (mut alive, mut unknown) = (0, 0);
+            for &neighbor in constraint.neighbors.iter() {
+                match self.values[neighbor] {
+                    Some(true) => alive += 1,
+                    Some(false) => {}
+                    None => unknown += 1,
+                }
+            }
+            let centers: &[bool] = match self.values[constraint.center] {
+                Some(true) => &[true],
+                Some(false) => &[false],
+                None => &[false, true],
+            };
+            centers.iter().any(|&center| {
+                (alive..=alive + unknown)
+                    .any(|count| (count == 3 || (center && count == 2)) == constraint.alive)
+            })
+        })
+    }
+
+    fn parent(&self) -> Universe {
+        let mut parent = Universe::empty(self.width, self.height);
+        let alive: Vec<(u32, u32)> = (0..self.values.len() as u32)
+            .filter(|&idx| self.values[idx as usize] == Some(true))
+            .map(|idx| (idx / self.width, idx % self.width))
+            .collect();
+        parent.set_cells(&alive);
+        parent
+    }
+}
+
+/// Searches for a parent of `target` until one is found, none can exist,
+/// or the time limit or cancel flag stops it.
+pub fn find_predecessor(
+    target: &Universe,
+    options: PredecessorOptions,
+) -> Result<Universe, PredecessorError> {
+    let mut search = PredecessorSearch::new(target, options);
+    loop {
+        if let Progress::Done(result) = search.run(CHECK_INTERVAL) {
+            return result;
+        }
+    }
+}
+
+// `Instant` isn't available in the browser, which has its own clock
+#[cfg(not(target_arch = "wasm32"))]
+struct Clock(std::time::Instant);
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Clock {
+    fn start() -> Clock {
+        Clock(std::time::Instant::now())
+    }
+
+    fn elapsed(&self) -> Duration {
+        self.0.elapsed()
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+struct Clock(f64);
+
+#[cfg(target_arch = "wasm32")]
+impl Clock {
+    fn start() -> Clock {
+        Clock(crate::utils::performance().now())
+    }
+
+    fn elapsed(&self) -> Duration {
+        Duration::from_secs_f64((crate::utils::performance().now() - self.0) / 1000f64)
+    }
+}
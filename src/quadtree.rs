@@ -0,0 +1,343 @@
+//! A hash-consed quadtree for patterns too large to hold as a dense `Universe`.
+//!
+//! Identical subtrees are stored once, so a pattern with millions of cells
+//! but lots of repetition only costs as much as its distinct 8x8 blocks and
+//! the branches above them.
+
+use std::collections::HashMap;
+
+use crate::cell::Cell;
+use crate::universe::Universe;
+
+pub type NodeId = usize;
+
+/// The all-dead node, valid at any level.
+pub const EMPTY: NodeId = 0;
+
+/// Level of an 8x8 leaf, whose side is `1 << LEAF_LEVEL` cells.
+pub const LEAF_LEVEL: u32 = 3;
+
+/// Highest level a tree may reach so every coordinate fits in a `u64`.
+pub const MAX_LEVEL: u32 = 62;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Node {
+    /// An 8x8 block with bit `row * 8 + column` set for each live cell.
+    Leaf(u64),
+    /// Four quadrants, each one level below `level`.
+    Branch {
+        level: u32,
+        nw: NodeId,
+        ne: NodeId,
+        sw: NodeId,
+        se: NodeId,
+    },
+}
+
+#[derive(Clone, Debug)]
+pub struct QuadTree {
+    nodes: Vec<Node>,
+    ids: HashMap<Node, NodeId>,
+    root: NodeId,
+    level: u32,
+}
+
+impl QuadTree {
+    pub fn new() -> QuadTree {
+        QuadTree {
+            nodes: vec![Node::Leaf(0)],
+            ids: HashMap::new(),
+            root: EMPTY,
+            level: LEAF_LEVEL,
+        }
+    }
+
+    /// Level of the root. The tree covers a square of `1 << level` cells.
+    pub fn level(&self) -> u32 {
+        self.level
+    }
+
+    pub fn side(&self) -> u64 {
+        1 << self.level
+    }
+
+    pub fn root(&self) -> NodeId {
+        self.root
+    }
+
+    pub fn node(&self, id: NodeId) -> Node {
+        self.nodes[id]
+    }
+
+    /// Number of distinct non-empty nodes stored.
+    pub fn node_count(&self) -> usize {
+        self.nodes.len() - 1
+    }
+
+    pub fn set_root(&mut self, root: NodeId, level: u32) {
+        self.root = root;
+        self.level = level;
+    }
+
+    fn intern(&mut self, node: Node) -> NodeId {
+        if let Some(&id) = self.ids.get(&node) {
+            return id;
+        }
+        let id = self.nodes.len();
+        self.nodes.push(node);
+        self.ids.insert(node, id);
+        id
+    }
+
+    pub fn leaf(&mut self, bits: u64) -> NodeId {
+        if bits == 0 {
+            return EMPTY;
+        }
+        self.intern(Node::Leaf(bits))
+    }
+
+    /// Returns the node at `level` with the given (nw, ne, sw, se) quadrants.
+    pub fn branch(&mut self, level: u32, quadrants: [NodeId; 4]) -> NodeId {
+        if quadrants.iter().all(|&id| id == EMPTY) {
+            return EMPTY;
+        }
+        let [nw, ne, sw, se] = quadrants;
+        self.intern(Node::Branch {
+            level,
+            nw,
+            ne,
+            sw,
+            se,
+        })
+    }
+
+    pub fn from_universe(universe: &Universe) -> QuadTree {
+        let mut tree = QuadTree::new();
+        let mut level = LEAF_LEVEL;
+        while (1u64 << level) < universe.width().max(universe.height()) as u64 {
+            level += 1;
+        }
+        let root = tree.build(universe, level, 0, 0);
+        tree.set_root(root, level);
+        tree
+    }
+
+    fn build(&mut self, universe: &Universe, level: u32, top: u64, left: u64) -> NodeId {
+        if top >= universe.height() as u64 || left >= universe.width() as u64 {
+            return EMPTY;
+        }
+
+        if level == LEAF_LEVEL {
+            let mut bits = 0;
+            for row in 0..8 {
+                for col in 0..8 {
+                    if universe_cell(universe, top + row, left + col) == Cell::Alive {
+                        bits |= 1 << (row * 8 + col);
+                    }
+                }
+            }
+            return self.leaf(bits);
+        }
+
+        let half = 1 << (level - 1);
+        let quadrants = [
+            self.build(universe, level - 1, top, left),
+            self.build(universe, level - 1, top, left + half),
+            self.build(universe, level - 1, top + half, left),
+            self.build(universe, level - 1, top + half, left + half),
+        ];
+        self.branch(level, quadrants)
+    }
+
+    pub fn get(&self, row: u64, column: u64) -> Cell {
+        if row >= self.side() || column >= self.side() {
+            return Cell::Dead;
+        }
+
+        let (mut id, mut row, mut column) = (self.root, row, column);
+        loop {
+            match self.nodes[id] {
+                _ if id == EMPTY => return Cell::Dead,
+                Node::Leaf(bits) => {
+                    return if bits & (1 << (row * 8 + column)) != 0 {
+                        Cell::Alive
+                    } else {
+                        Cell::Dead
+                    };
+                }
+                Node::Branch {
+                    level,
+                    nw,
+                    ne,
+                    sw,
+                    se,
+                } => {
+                    let half = 1 << (level - 1);
+                    id = match (row >= half, column >= half) {
+                        (false, false) => nw,
+                        (false, true) => ne,
+                        (true, false) => sw,
+                        (true, true) => se,
+                    };
+                    row %= half;
+                    column %= half;
+                }
+            }
+        }
+    }
+
+    pub fn population(&self) -> u64 {
+        fn count(tree: &QuadTree, id: NodeId, memo: &mut HashMap<NodeId, u64>) -> u64 {
+            if let Some(&population) = memo.get(&id) {
+                return population;
+            }
+            let population = match tree.nodes[id] {
+                _ if id == EMPTY => 0,
+                Node::Leaf(bits) => bits.count_ones() as u64,
+                Node::Branch { nw, ne, sw, se, .. } => [nw, ne, sw, se]
+                    .iter()
+                    .map(|&child| count(tree, child, memo))
+                    .sum(),
+            };
+            memo.insert(id, population);
+            population
+        }
+
+        count(self, self.root, &mut HashMap::new())
+    }
+
+    /// Returns the smallest (row, column, height, width) rectangle holding
+    /// every live cell, or `None` when the tree is empty.
+    pub fn bounding_box(&self) -> Option<(u64, u64, u64, u64)> {
+        type Bounds = Option<(u64, u64, u64, u64)>;
+
+        // Inclusive (top, left, bottom, right) bounds relative to the node
+        fn bounds(tree: &QuadTree, id: NodeId, memo: &mut HashMap<NodeId, Bounds>) -> Bounds {
+            if let Some(&cached) = memo.get(&id) {
+                return cached;
+            }
+            let result = match tree.nodes[id] {
+                _ if id == EMPTY => None,
+                Node::Leaf(bits) => {
+                    let cells = (0..64u64).filter(|bit| bits & (1 << bit) != 0);
+                    cells.fold(None, |acc, bit| {
+                        union(acc, (bit / 8, bit % 8, bit / 8, bit % 8))
+                    })
+                }
+                Node::Branch {
+                    level,
+                    nw,
+                    ne,
+                    sw,
+                    se,
+                } => {
+                    let half = 1 << (level - 1);
+                    let offsets = [(nw, 0, 0), (ne, 0, half), (sw, half, 0), (se, half, half)];
+                    let mut result = None;
+                    for &(child, row, col) in offsets.iter() {
+                        if let Some((top, left, bottom, right)) = bounds(tree, child, memo) {
+                            result =
+                                union(result, (top + row, left + col, bottom + row, right + col));
+                        }
+                    }
+                    result
+                }
+            };
+            memo.insert(id, result);
+            result
+        }
+
+        fn union(acc: Bounds, (top, left, bottom, right): (u64, u64, u64, u64)) -> Bounds {
+            Some(match acc {
+                None => (top, left, bottom, right),
+                Some((t, l, b, r)) => (t.min(top), l.min(left), b.max(bottom), r.max(right)),
+            })
+        }
+
+        bounds(self, self.root, &mut HashMap::new())
+            .map(|(top, left, bottom, right)| (top, left, bottom - top + 1, right - left + 1))
+    }
+
+    /// Copies the `width` x `height` window whose top left corner is at
+    /// (`row`, `column`) into a dense universe. Only the nodes overlapping
+    /// the window are visited.
+    pub fn window(&self, row: u64, column: u64, height: u32, width: u32) -> Universe {
+        let mut universe = Universe::empty(width, height);
+        let window = Window {
+            row,
+            column,
+            height: height as u64,
+            width: width as u64,
+        };
+        let mut alive = vec![];
+        self.collect(self.root, self.level, 0, 0, &window, &mut alive);
+        universe.set_cells(&alive);
+        universe
+    }
+
+    fn collect(
+        &self,
+        id: NodeId,
+        level: u32,
+        top: u64,
+        left: u64,
+        window: &Window,
+        alive: &mut Vec<(u32, u32)>,
+    ) {
+        let side = 1u64 << level;
+        let overlaps = top < window.row + window.height
+            && window.row < top + side
+            && left < window.column + window.width
+            && window.column < left + side;
+        if id == EMPTY || !overlaps {
+            return;
+        }
+
+        match self.nodes[id] {
+            Node::Leaf(bits) => {
+                for bit in (0..64u64).filter(|bit| bits & (1 << bit) != 0) {
+                    let (row, col) = (top + bit / 8, left + bit % 8);
+                    if window.contains(row, col) {
+                        alive.push(((row - window.row) as u32, (col - window.column) as u32));
+                    }
+                }
+            }
+            Node::Branch { nw, ne, sw, se, .. } => {
+                let half = side / 2;
+                self.collect(nw, level - 1, top, left, window, alive);
+                self.collect(ne, level - 1, top, left + half, window, alive);
+                self.collect(sw, level - 1, top + half, left, window, alive);
+                self.collect(se, level - 1, top + half, left + half, window, alive);
+            }
+        }
+    }
+}
+
+impl Default for QuadTree {
+    fn default() -> QuadTree {
+        QuadTree::new()
+    }
+}
+
+struct Window {
+    row: u64,
+    column: u64,
+    height: u64,
+    width: u64,
+}
+
+impl Window {
+    fn contains(&self, row: u64, column: u64) -> bool {
+        row >= self.row
+            && row < self.row + self.height
+            && column >= self.column
+            && column < self.column + self.width
+    }
+}
+
+fn universe_cell(universe: &Universe, row: u64, column: u64) -> Cell {
+    if row >= universe.height() as u64 || column >= universe.width() as u64 {
+        return Cell::Dead;
+    }
+    universe.cells()[universe.get_index(row as u32, column as u32)]
+}
@@ -0,0 +1,158 @@
+//! Finding every copy of a pattern on the board, in any orientation.
+//!
+//! A match needs the pattern's live cells alive and the rest of its
+//! bounding box dead. With `SearchOptions::padding`, a border that many
+//! cells wide around the bounding box must be dead too, so a glider that is
+//! part of some larger object doesn't count. Matches may wrap around the
+//! edges like everything else on the board.
+
+use crate::cell::Cell;
+use crate::universe::{Region, Universe};
+
+/// One of the eight ways to rotate or reflect a pattern.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Orientation {
+    Identity,
+    /// A quarter turn clockwise.
+    Rotate90,
+    Rotate180,
+    Rotate270,
+    FlipHorizontal,
+    FlipVertical,
+    /// Mirrored along the main diagonal.
+    Transpose,
+    /// Mirrored along the other diagonal.
+    AntiTranspose,
+}
+
+impl Orientation {
+    /// Every orientation, in the order `search` tries them.
+    pub const ALL: [Orientation; 8] = [
+        Orientation::Identity,
+        Orientation::Rotate90,
+        Orientation::Rotate180,
+        Orientation::Rotate270,
+        Orientation::FlipHorizontal,
+        Orientation::FlipVertical,
+        Orientation::Transpose,
+        Orientation::AntiTranspose,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Orientation::Identity => "as is",
+            Orientation::Rotate90 => "rotated 90°",
+            Orientation::Rotate180 => "rotated 180°",
+            Orientation::Rotate270 => "rotated 270°",
+            Orientation::FlipHorizontal => "flipped left to right",
+            Orientation::FlipVertical => "flipped top to bottom",
+            Orientation::Transpose => "mirrored on the main diagonal",
+            Orientation::AntiTranspose => "mirrored on the other diagonal",
+        }
+    }
+
+    pub fn apply(self, pattern: &Universe) -> Universe {
+        match self {
+            Orientation::Identity => pattern.clone(),
+            Orientation::Rotate90 => pattern.rotate_90(),
+            Orientation::Rotate180 => pattern.rotate_180(),
+            Orientation::Rotate270 => pattern.rotate_270(),
+            Orientation::FlipHorizontal => pattern.flip_horizontal(),
+            Orientation::FlipVertical => pattern.flip_vertical(),
+            Orientation::Transpose => pattern.transpose(),
+            Orientation::AntiTranspose => pattern.transpose().rotate_180(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SearchOptions {
+    /// Width of the dead border a match needs around it, 0 for none.
+    pub padding: u32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Match {
+    /// Where the pattern's bounding box lies. It may wrap around the edges.
+    pub region: Region,
+    /// How the pattern was turned to match. Symmetric patterns only report
+    /// the first of `Orientation::ALL` that fits.
+    pub orientation: Orientation,
+}
+
+/// Finds every copy of `pattern` on `board`, sorted by row then column.
+/// Dead cells around the pattern are ignored, and an empty pattern matches
+/// nothing.
+pub fn search(board: &Universe, pattern: &Universe, options: &SearchOptions) -> Vec<Match> {
+    let pattern = pattern.crop();
+    if pattern.width() == 0 {
+        return vec![];
+    }
+
+    // Symmetric patterns look the same in several orientations
+    let mut oriented: Vec<(Orientation, Universe)> = vec![];
+    for &orientation in Orientation::ALL.iter() {
+        let candidate = orientation.apply(&pattern);
+        if !oriented.iter().any(|(_, seen)| *seen == candidate) {
+            oriented.push((orientation, candidate));
+        }
+    }
+
+    let mut matches = vec![];
+    for row in 0..board.height() {
+        for col in 0..board.width() {
+            for (orientation, candidate) in oriented.iter() {
+                if fits(board, candidate, row, col, options.padding) {
+                    matches.push(Match {
+                        region: Region {
+                            row,
+                            column: col,
+                            height: candidate.height(),
+                            width: candidate.width(),
+                        },
+                        orientation: *orientation,
+                    });
+                    break;
+                }
+            }
+        }
+    }
+    matches
+}
+
+// Whether `pattern` with a dead border `padding` wide matches the board
+// with its top left corner at (`row`, `col`)
+fn fits(board: &Universe, pattern: &Universe, row: u32, col: u32, padding: u32) -> bool {
+    // A pattern wider than the board would overlap itself across the edge
+    if pattern.height() + 2 * padding > board.height()
+        || pattern.width() + 2 * padding > board.width()
+    {
+        return false;
+    }
+
+    let padding = padding as i64;
+    for d_row in -padding..pattern.height() as i64 + padding {
+        for d_col in -padding..pattern.width() as i64 + padding {
+            let inside = (0..pattern.height() as i64).contains(&d_row)
+                && (0..pattern.width() as i64).contains(&d_col);
+            let expected = if inside {
+                pattern.cells()[pattern.get_index(d_row as u32, d_col as u32)]
+            } else {
+                Cell::Dead
+            };
+            let board_row = (row as i64 + d_row).rem_euclid(board.height() as i64) as u32;
+            let board_col = (col as i64 + d_col).rem_euclid(board.width() as i64) as u32;
+            if board.cells()[board.get_index(board_row, board_col)] != expected {
+                return false;
+            }
+        }
+    }
+    true
+}
+
+impl Universe {
+    /// Finds every copy of `pattern` on the board. See `search::search`.
+    pub fn search(&self, pattern: &Universe, options: &SearchOptions) -> Vec<Match> {
+        search(self, pattern, options)
+    }
+}
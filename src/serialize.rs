@@ -0,0 +1,152 @@
+//! `serde` support, enabled by the `serde` feature.
+//!
+//! A `Cell` is written as `0` or `1`. A `Universe` is written as its size,
+//! generation and a run-length string of its cells in row-major order, so
+//! a mostly empty board stays small:
+//!
+//! ```json
+//! {"width": 3, "height": 3, "generation": 0, "cells": "bo3b4o"}
+//! ```
+//!
+//! Each run is an optional count followed by `b` for dead or `o` for live
+//! cells, as in the body of an RLE file but without row breaks.
+
+use std::fmt::Write;
+
+use serde::de::{self, Deserialize, Deserializer, Unexpected};
+use serde::ser::{Serialize, Serializer};
+
+use crate::cell::Cell;
+use crate::formats::MAX_CELLS;
+use crate::universe::Universe;
+
+impl Serialize for Cell {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(*self as u8)
+    }
+}
+
+impl<'de> Deserialize<'de> for Cell {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Cell, D::Error> {
+        match u8::deserialize(deserializer)? {
+            0 => Ok(Cell::Dead),
+            1 => Ok(Cell::Alive),
+            value => Err(de::Error::invalid_value(
+                Unexpected::Unsigned(value as u64),
+                &"0 or 1",
+            )),
+        }
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct UniverseFields {
+    width: u32,
+    height: u32,
+    #[serde(default)]
+    generation: u64,
+    cells: String,
+}
+
+impl Serialize for Universe {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        UniverseFields {
+            width: self.width(),
+            height: self.height(),
+            generation: self.generation(),
+            cells: encode_cells(self.cells()),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Universe {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Universe, D::Error> {
+        let fields = UniverseFields::deserialize(deserializer)?;
+        let count = fields.width as u64 * fields.height as u64;
+        if count > MAX_CELLS {
+            return Err(de::Error::custom(format!(
+                "{}x{} board is too large",
+                fields.width, fields.height
+            )));
+        }
+        let alive = decode_cells(&fields.cells, count).map_err(de::Error::custom)?;
+
+        let mut universe = Universe::empty(fields.width, fields.height);
+        let alive: Vec<(u32, u32)> = alive
+            .into_iter()
+            .map(|idx| {
+                let width = fields.width as u64;
+                ((idx / width) as u32, (idx % width) as u32)
+            })
+            .collect();
+        universe.set_cells(&alive);
+        universe.set_generation(fields.generation);
+        Ok(universe)
+    }
+}
+
+fn encode_cells(cells: &[Cell]) -> String {
+    let mut out = String::new();
+    let mut runs: Vec<(Cell, u64)> = vec![];
+    for &cell in cells.iter() {
+        match runs.last_mut() {
+            Some((state, length)) if *state == cell => *length += 1,
+            _ => runs.push((cell, 1)),
+        }
+    }
+    for (cell, length) in runs {
+        if length > 1 {
+            write!(out, "{}", length).unwrap();
+        }
+        out.push(if cell == Cell::Alive { 'o' } else { 'b' });
+    }
+    out
+}
+
+// Indices of the live cells, checking the runs cover exactly `count` cells
+fn decode_cells(text: &str, count: u64) -> Result<Vec<u64>, String> {
+    let mut alive = vec![];
+    let mut idx = 0u64;
+    let mut run: Option<u64> = None;
+
+    for ch in text.chars() {
+        match ch {
+            '0'..='9' => {
+                let digit = ch.to_digit(10).unwrap() as u64;
+                run = Some(
+                    run.unwrap_or(0)
+                        .checked_mul(10)
+                        .and_then(|value| value.checked_add(digit))
+                        .filter(|&value| value <= count)
+                        .ok_or_else(|| String::from("cell run is longer than the board"))?,
+                );
+            }
+            'b' | 'o' => {
+                let length = run.take().unwrap_or(1);
+                if length > count - idx {
+                    return Err(format!(
+                        "cells don't match the board size: more than {}",
+                        count
+                    ));
+                }
+                if ch == 'o' {
+                    alive.extend(idx..idx + length);
+                }
+                idx += length;
+            }
+            ch => return Err(format!("unexpected character '{}' in cells", ch)),
+        }
+    }
+
+    if run.is_some() {
+        return Err(String::from("cell run is missing its tag"));
+    }
+    if idx != count {
+        return Err(format!(
+            "cells don't match the board size: expected {}, found {}",
+            count, idx
+        ));
+    }
+    Ok(alive)
+}
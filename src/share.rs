@@ -0,0 +1,132 @@
+//! Boards encoded in a URL, so a link can open the app on a given pattern.
+//!
+//! The location hash holds `&`-separated `key=value` pairs:
+//!
+//! ```text
+//! #rle=eCA9IDMsIHkgPSAzLCBydWxlID0gQjMvUzIzCmJvJDJibyQzbyEK&speed=2
+//! ```
+//!
+//! `rle` is the board as an RLE file, including its size and rule, in
+//! unpadded URL-safe base64. `speed` is the number of generations per frame.
+//! Unknown keys are ignored so newer links still open in older builds.
+
+use std::error::Error;
+use std::fmt;
+
+use crate::base64;
+use crate::formats::{rle, ParseError, Pattern};
+
+/// Longest hash `SharedBoard::from_hash` will decode.
+pub const MAX_HASH_LENGTH: usize = 100_000;
+
+/// Largest side, in cells, of a shared board.
+pub const MAX_SIDE: u32 = 1024;
+
+pub const MAX_SPEED: u32 = 10;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SharedBoard {
+    pub pattern: Pattern,
+    /// Generations per frame, from 1 to `MAX_SPEED`.
+    pub speed: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ShareError {
+    TooLong(usize),
+    /// A key is missing or its value can't be read.
+    Malformed(String),
+    Pattern(ParseError),
+    TooLarge {
+        width: u32,
+        height: u32,
+    },
+}
+
+impl fmt::Display for ShareError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ShareError::TooLong(length) => write!(
+                f,
+                "link is {} characters long, more than {}",
+                length, MAX_HASH_LENGTH
+            ),
+            ShareError::Malformed(message) => write!(f, "malformed link: {}", message),
+            ShareError::Pattern(error) => write!(f, "link pattern: {}", error),
+            ShareError::TooLarge { width, height } => {
+                write!(f, "{}x{} board is too large", width, height)
+            }
+        }
+    }
+}
+
+impl Error for ShareError {}
+
+impl From<ParseError> for ShareError {
+    fn from(error: ParseError) -> ShareError {
+        ShareError::Pattern(error)
+    }
+}
+
+impl SharedBoard {
+    /// The hash for this board, without the leading `#`.
+    pub fn to_hash(&self) -> String {
+        format!(
+            "rle={}&speed={}",
+            base64::encode(rle::write(&self.pattern).as_bytes()),
+            self.speed
+        )
+    }
+
+    /// Reads a board from `hash`, with or without its leading `#`.
+    pub fn from_hash(hash: &str) -> Result<SharedBoard, ShareError> {
+        let hash = hash.strip_prefix('#').unwrap_or(hash);
+        if hash.len() > MAX_HASH_LENGTH {
+            return Err(ShareError::TooLong(hash.len()));
+        }
+
+        let (mut pattern, mut speed) = (None, 1);
+        for pair in hash.split('&').filter(|pair| !pair.is_empty()) {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            match key {
+                "rle" => {
+                    let bytes = base64::decode(value)
+                        .ok_or_else(|| malformed("`rle` isn't valid base64"))?;
+                    let text = String::from_utf8(bytes)
+                        .map_err(|_| malformed("`rle` isn't valid UTF-8"))?;
+                    // The header is checked first so an oversized board is
+                    // never decoded. Links always carry one, and without it
+                    // the size isn't known until every cell is read
+                    let (width, height) = rle::size(&text)?
+                        .ok_or_else(|| malformed("`rle` is missing its size header"))?;
+                    check_size(width, height)?;
+                    pattern = Some(rle::parse(&text)?);
+                }
+                "speed" => {
+                    speed = value
+                        .parse()
+                        .ok()
+                        .filter(|speed| (1..=MAX_SPEED).contains(speed))
+                        .ok_or_else(|| malformed(&format!("`speed` must be 1 to {}", MAX_SPEED)))?;
+                }
+                _ => {}
+            }
+        }
+
+        let pattern = pattern.ok_or_else(|| malformed("missing `rle`"))?;
+        check_size(pattern.universe.width(), pattern.universe.height())?;
+
+        Ok(SharedBoard { pattern, speed })
+    }
+}
+
+fn check_size(width: u32, height: u32) -> Result<(), ShareError> {
+    if width == 0 || height == 0 || width > MAX_SIDE || height > MAX_SIDE {
+        return Err(ShareError::TooLarge { width, height });
+    }
+    Ok(())
+}
+
+fn malformed(message: &str) -> ShareError {
+    ShareError::Malformed(message.to_string())
+}
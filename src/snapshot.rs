@@ -0,0 +1,344 @@
+//! A compact binary snapshot of the full simulation state.
+//!
+//! All integers are little endian:
+//!
+//! ```text
+//! magic      4 bytes  "GOLS"
+//! version    u8
+//! encoding   u8       0 = one bit per cell, 1 = run lengths
+//! width      u32
+//! height     u32
+//! generation u64
+//! topology   u8       0 = torus
+//! rule       u16 length, then UTF-8 bytes
+//! cells      u32 length, then the encoded cells
+//! checksum   u32      CRC-32 of every byte before it
+//! ```
+//!
+//! Bit-packed cells fill each byte from the lowest bit, in row-major order.
+//! Run-length cells are LEB128 varints giving alternating runs of dead and
+//! live cells, starting with dead. The writer picks whichever is smaller.
+//!
+//! Each new version gets its own decoder, so snapshots written by older
+//! builds keep loading after the format grows.
+
+use std::error::Error;
+use std::fmt;
+
+use crate::cell::Cell;
+use crate::checksum::crc32;
+use crate::formats::{rle, MAX_CELLS};
+use crate::universe::Universe;
+
+pub const MAGIC: &[u8; 4] = b"GOLS";
+
+/// Version written by `Snapshot::encode`.
+pub const VERSION: u8 = 1;
+
+const BIT_PACKED: u8 = 0;
+const RUN_LENGTH: u8 = 1;
+
+/// How the edges of the board behave.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Topology {
+    /// Opposite edges are joined, as in `Universe::tick`.
+    Torus,
+}
+
+impl Topology {
+    fn code(self) -> u8 {
+        match self {
+            Topology::Torus => 0,
+        }
+    }
+
+    fn from_code(code: u8) -> Option<Topology> {
+        match code {
+            0 => Some(Topology::Torus),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Snapshot {
+    /// The board, including its generation count.
+    pub universe: Universe,
+    pub rule: String,
+    pub topology: Topology,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SnapshotError {
+    /// The input ended before the snapshot did.
+    Truncated,
+    /// The input doesn't start with `MAGIC`.
+    BadMagic,
+    /// The snapshot was written by a newer version of the format.
+    UnsupportedVersion(u8),
+    ChecksumMismatch {
+        expected: u32,
+        actual: u32,
+    },
+    /// The checksum matched but a field holds an impossible value.
+    Corrupt(String),
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SnapshotError::Truncated => write!(f, "snapshot is truncated"),
+            SnapshotError::BadMagic => write!(f, "not a snapshot"),
+            SnapshotError::UnsupportedVersion(version) => {
+                write!(f, "unsupported snapshot version {}", version)
+            }
+            SnapshotError::ChecksumMismatch { expected, actual } => write!(
+                f,
+                "snapshot checksum mismatch: expected {:08x}, found {:08x}",
+                expected, actual
+            ),
+            SnapshotError::Corrupt(message) => write!(f, "snapshot is corrupt: {}", message),
+        }
+    }
+}
+
+impl Error for SnapshotError {}
+
+impl Snapshot {
+    /// A snapshot of `universe` under the default rule on a torus.
+    pub fn new(universe: Universe) -> Snapshot {
+        Snapshot {
+            universe,
+            rule: rle::DEFAULT_RULE.to_string(),
+            topology: Topology::Torus,
+        }
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let universe = &self.universe;
+        let bits = pack_bits(universe.cells());
+        let runs = encode_runs(universe.cells());
+        let (encoding, cells) = if runs.len() < bits.len() {
+            (RUN_LENGTH, runs)
+        } else {
+            (BIT_PACKED, bits)
+        };
+        let rule = &self.rule.as_bytes()[..self.rule.len().min(u16::MAX as usize)];
+
+        let mut out = Vec::with_capacity(32 + rule.len() + cells.len());
+        out.extend_from_slice(MAGIC);
+        out.push(VERSION);
+        out.push(encoding);
+        out.extend_from_slice(&universe.width().to_le_bytes());
+        out.extend_from_slice(&universe.height().to_le_bytes());
+        out.extend_from_slice(&universe.generation().to_le_bytes());
+        out.push(self.topology.code());
+        out.extend_from_slice(&(rule.len() as u16).to_le_bytes());
+        out.extend_from_slice(rule);
+        out.extend_from_slice(&(cells.len() as u32).to_le_bytes());
+        out.extend_from_slice(&cells);
+        let checksum = crc32(&out);
+        out.extend_from_slice(&checksum.to_le_bytes());
+        out
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Snapshot, SnapshotError> {
+        let mut reader = Reader { bytes, position: 0 };
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err(SnapshotError::BadMagic);
+        }
+        match reader.u8()? {
+            1 => decode_v1(reader),
+            version => Err(SnapshotError::UnsupportedVersion(version)),
+        }
+    }
+}
+
+fn decode_v1(mut reader: Reader) -> Result<Snapshot, SnapshotError> {
+    let encoding = reader.u8()?;
+    let width = reader.u32()?;
+    let height = reader.u32()?;
+    let generation = reader.u64()?;
+    let topology = reader.u8()?;
+    let rule_len = reader.u16()? as usize;
+    let rule = reader.take(rule_len)?;
+    let cells_len = reader.u32()? as usize;
+    let cells = reader.take(cells_len)?;
+
+    let end = reader.position;
+    let expected = reader.u32()?;
+    let actual = crc32(&reader.bytes[..end]);
+    if expected != actual {
+        return Err(SnapshotError::ChecksumMismatch { expected, actual });
+    }
+    if reader.position != reader.bytes.len() {
+        return Err(corrupt("unexpected bytes after the checksum"));
+    }
+
+    let topology = Topology::from_code(topology)
+        .ok_or_else(|| corrupt(&format!("unknown topology {}", topology)))?;
+    let rule = String::from_utf8(rule.to_vec()).map_err(|_| corrupt("rule is not UTF-8"))?;
+    let count = width as u64 * height as u64;
+    if count > MAX_CELLS {
+        return Err(corrupt(&format!("{}x{} board is too large", width, height)));
+    }
+    let alive = match encoding {
+        BIT_PACKED => unpack_bits(cells, count as usize)?,
+        RUN_LENGTH => decode_runs(cells, count as usize)?,
+        _ => return Err(corrupt(&format!("unknown cell encoding {}", encoding))),
+    };
+
+    let mut universe = Universe::empty(width, height);
+    let alive: Vec<(u32, u32)> = alive
+        .into_iter()
+        .map(|idx| ((idx / width as usize) as u32, (idx % width as usize) as u32))
+        .collect();
+    universe.set_cells(&alive);
+    universe.set_generation(generation);
+
+    Ok(Snapshot {
+        universe,
+        rule,
+        topology,
+    })
+}
+
+fn corrupt(message: &str) -> SnapshotError {
+    SnapshotError::Corrupt(message.to_string())
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], SnapshotError> {
+        let end = self
+            .position
+            .checked_add(len)
+            .filter(|&end| end <= self.bytes.len())
+            .ok_or(SnapshotError::Truncated)?;
+        let bytes = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], SnapshotError> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.take(N)?);
+        Ok(array)
+    }
+
+    fn u8(&mut self) -> Result<u8, SnapshotError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, SnapshotError> {
+        self.array().map(u16::from_le_bytes)
+    }
+
+    fn u32(&mut self) -> Result<u32, SnapshotError> {
+        self.array().map(u32::from_le_bytes)
+    }
+
+    fn u64(&mut self) -> Result<u64, SnapshotError> {
+        self.array().map(u64::from_le_bytes)
+    }
+}
+
+fn pack_bits(cells: &[Cell]) -> Vec<u8> {
+    let mut bytes = vec![0; cells.len().div_ceil(8)];
+    for (idx, &cell) in cells.iter().enumerate() {
+        if cell == Cell::Alive {
+            bytes[idx / 8] |= 1 << (idx % 8);
+        }
+    }
+    bytes
+}
+
+// Indices of the live cells among the first `count` bits
+fn unpack_bits(bytes: &[u8], count: usize) -> Result<Vec<usize>, SnapshotError> {
+    if bytes.len() != count.div_ceil(8) {
+        return Err(corrupt("cell data doesn't match the board size"));
+    }
+    Ok((0..count)
+        .filter(|idx| bytes[idx / 8] & (1 << (idx % 8)) != 0)
+        .collect())
+}
+
+fn encode_runs(cells: &[Cell]) -> Vec<u8> {
+    let mut out = vec![];
+    let mut state = Cell::Dead;
+    let mut run = 0u64;
+    for &cell in cells.iter() {
+        if cell != state {
+            push_varint(&mut out, run);
+            state = cell;
+            run = 0;
+        }
+        run += 1;
+    }
+    if run > 0 {
+        push_varint(&mut out, run);
+    }
+    out
+}
+
+fn decode_runs(bytes: &[u8], count: usize) -> Result<Vec<usize>, SnapshotError> {
+    let mut alive = vec![];
+    let mut idx = 0usize;
+    let mut live = false;
+    let mut position = 0;
+    while position < bytes.len() {
+        let run = read_varint(bytes, &mut position)? as usize;
+        if run > count - idx {
+            return Err(corrupt("cell runs extend past the board"));
+        }
+        if live {
+            alive.extend(idx..idx + run);
+        }
+        idx += run;
+        live = !live;
+    }
+    if idx != count {
+        return Err(corrupt("cell runs don't cover the board"));
+    }
+    Ok(alive)
+}
+
+fn push_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn read_varint(bytes: &[u8], position: &mut usize) -> Result<u64, SnapshotError> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let byte = *bytes
+            .get(*position)
+            .ok_or_else(|| corrupt("cell run is cut short"))?;
+        *position += 1;
+        value |= ((byte & 0x7F) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(corrupt("cell run is too long"))
+}
+
+impl Universe {
+    /// Encodes the universe as a snapshot under the default rule.
+    pub fn to_snapshot(&self) -> Vec<u8> {
+        Snapshot::new(self.clone()).encode()
+    }
+
+    pub fn from_snapshot(bytes: &[u8]) -> Result<Universe, SnapshotError> {
+        Snapshot::decode(bytes).map(|snapshot| snapshot.universe)
+    }
+}
@@ -0,0 +1,217 @@
+//! Named save slots kept in `window.localStorage`.
+//!
+//! Each slot is stored under `SLOT_PREFIX` followed by its name, as
+//! `&`-separated `key=value` pairs like the shared link hash: `speed` and a
+//! base64 `snapshot` in the binary snapshot format. The last session is kept
+//! the same way under `AUTOSAVE_KEY`, with the location hash written
+//! alongside it under `AUTOSAVE_HASH_KEY`.
+
+use std::error::Error;
+use std::fmt;
+
+use wasm_bindgen::JsValue;
+
+use crate::base64;
+use crate::snapshot::{Snapshot, SnapshotError};
+use crate::utils::window;
+
+pub const SLOT_PREFIX: &str = "wasm-game-of-life/slot/";
+pub const AUTOSAVE_KEY: &str = "wasm-game-of-life/autosave";
+pub const AUTOSAVE_HASH_KEY: &str = "wasm-game-of-life/autosave-hash";
+
+/// A board along with the settings it was saved with.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SavedBoard {
+    pub snapshot: Snapshot,
+    /// Generations per frame.
+    pub speed: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SaveError {
+    /// The browser has no `localStorage`, or it is disabled.
+    Unavailable,
+    /// The browser refused the write, usually because storage is full.
+    Storage(String),
+    NotFound(String),
+    AlreadyExists(String),
+    InvalidName,
+    Malformed(String),
+    Snapshot(SnapshotError),
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SaveError::Unavailable => write!(f, "local storage is unavailable"),
+            SaveError::Storage(reason) => write!(f, "the browser refused to save: {}", reason),
+            SaveError::NotFound(name) => write!(f, "no save called \"{}\"", name),
+            SaveError::AlreadyExists(name) => write!(f, "a save called \"{}\" exists", name),
+            SaveError::InvalidName => write!(f, "save names can't be blank"),
+            SaveError::Malformed(message) => write!(f, "malformed save: {}", message),
+            SaveError::Snapshot(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl Error for SaveError {}
+
+impl From<SnapshotError> for SaveError {
+    fn from(error: SnapshotError) -> SaveError {
+        SaveError::Snapshot(error)
+    }
+}
+
+impl SavedBoard {
+    /// The string stored in `localStorage`.
+    pub fn to_value(&self) -> String {
+        format!(
+            "speed={}&snapshot={}",
+            self.speed,
+            base64::encode(&self.snapshot.encode())
+        )
+    }
+
+    pub fn from_value(value: &str) -> Result<SavedBoard, SaveError> {
+        let malformed = |message: &str| SaveError::Malformed(message.to_string());
+        let (mut snapshot, mut speed) = (None, 1);
+        for pair in value.split('&').filter(|pair| !pair.is_empty()) {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            match key {
+                "snapshot" => {
+                    let bytes = base64::decode(value)
+                        .ok_or_else(|| malformed("`snapshot` isn't valid base64"))?;
+                    snapshot = Some(Snapshot::decode(&bytes)?);
+                }
+                "speed" => {
+                    speed = value
+                        .parse()
+                        .map_err(|_| malformed("`speed` isn't a number"))?
+                }
+                _ => {}
+            }
+        }
+        let snapshot = snapshot.ok_or_else(|| malformed("missing `snapshot`"))?;
+        Ok(SavedBoard { snapshot, speed })
+    }
+}
+
+/// The storage key for the slot called `name`.
+pub fn slot_key(name: &str) -> String {
+    format!("{}{}", SLOT_PREFIX, name)
+}
+
+fn local_storage() -> Result<web_sys::Storage, SaveError> {
+    window()
+        .local_storage()
+        .ok()
+        .flatten()
+        .ok_or(SaveError::Unavailable)
+}
+
+// Names the exception, e.g. `QuotaExceededError`, instead of failing
+fn storage_error(error: JsValue) -> SaveError {
+    let name = js_sys::Reflect::get(&error, &JsValue::from_str("name"))
+        .ok()
+        .and_then(|name| name.as_string());
+    match name.as_deref() {
+        Some("QuotaExceededError") => SaveError::Storage(String::from("storage is full")),
+        Some(name) => SaveError::Storage(name.to_string()),
+        None => SaveError::Storage(format!("{:?}", error)),
+    }
+}
+
+fn read(key: &str) -> Result<Option<SavedBoard>, SaveError> {
+    let storage = local_storage()?;
+    match storage.get_item(key).map_err(storage_error)? {
+        Some(value) => SavedBoard::from_value(&value).map(Some),
+        None => Ok(None),
+    }
+}
+
+fn write(key: &str, board: &SavedBoard) -> Result<(), SaveError> {
+    local_storage()?
+        .set_item(key, &board.to_value())
+        .map_err(storage_error)
+}
+
+/// Names of every saved slot, sorted.
+pub fn slot_names() -> Result<Vec<String>, SaveError> {
+    let storage = local_storage()?;
+    let length = storage.length().map_err(storage_error)?;
+    let mut names: Vec<String> = (0..length)
+        .filter_map(|idx| storage.key(idx).ok().flatten())
+        .filter_map(|key| key.strip_prefix(SLOT_PREFIX).map(String::from))
+        .collect();
+    names.sort();
+    Ok(names)
+}
+
+/// Saves `board` as `name`, replacing any slot with that name.
+pub fn save(name: &str, board: &SavedBoard) -> Result<(), SaveError> {
+    if name.trim().is_empty() {
+        return Err(SaveError::InvalidName);
+    }
+    write(&slot_key(name), board)
+}
+
+pub fn load(name: &str) -> Result<SavedBoard, SaveError> {
+    read(&slot_key(name))?.ok_or_else(|| SaveError::NotFound(name.to_string()))
+}
+
+pub fn rename(from: &str, to: &str) -> Result<(), SaveError> {
+    if to.trim().is_empty() {
+        return Err(SaveError::InvalidName);
+    }
+    let storage = local_storage()?;
+    let value = storage
+        .get_item(&slot_key(from))
+        .map_err(storage_error)?
+        .ok_or_else(|| SaveError::NotFound(from.to_string()))?;
+    if storage
+        .get_item(&slot_key(to))
+        .map_err(storage_error)?
+        .is_some()
+    {
+        return Err(SaveError::AlreadyExists(to.to_string()));
+    }
+    storage
+        .set_item(&slot_key(to), &value)
+        .map_err(storage_error)?;
+    storage.remove_item(&slot_key(from)).map_err(storage_error)
+}
+
+pub fn delete(name: &str) -> Result<(), SaveError> {
+    local_storage()?
+        .remove_item(&slot_key(name))
+        .map_err(storage_error)
+}
+
+/// Saves the session along with `hash`, the location hash written for it,
+/// empty when the address bar was left without one.
+pub fn autosave(board: &SavedBoard, hash: &str) -> Result<(), SaveError> {
+    write(AUTOSAVE_KEY, board)?;
+    local_storage()?
+        .set_item(AUTOSAVE_HASH_KEY, hash)
+        .map_err(storage_error)
+}
+
+/// The last session's board, if one was saved and `location_hash` doesn't
+/// hold a different board, such as a link shared from elsewhere.
+pub fn restore_autosave(location_hash: &str) -> Result<Option<SavedBoard>, SaveError> {
+    let saved_hash = local_storage()?
+        .get_item(AUTOSAVE_HASH_KEY)
+        .map_err(storage_error)?;
+    if !autosave_matches(location_hash, saved_hash.as_deref()) {
+        return Ok(None);
+    }
+    read(AUTOSAVE_KEY)
+}
+
+/// Whether the autosave, written along with `saved_hash`, is the session the
+/// page was opened on. A hash the app wrote itself is stale after a reload,
+/// since it holds no generation count; any other hash came from a link.
+pub fn autosave_matches(location_hash: &str, saved_hash: Option<&str>) -> bool {
+    let hash = location_hash.strip_prefix('#').unwrap_or(location_hash);
+    hash.is_empty() || saved_hash == Some(hash)
+}
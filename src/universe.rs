@@ -1,11 +1,135 @@
 use std::fmt;
+use std::str::FromStr;
 
 use crate::cell::Cell;
+use crate::formats::ParseError;
+
+/// How the cells of a stamped pattern combine with the cells already on the
+/// board.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StampMode {
+    /// Pattern cells are added, existing live cells are kept.
+    Or,
+    /// Pattern cells toggle the board cells underneath them.
+    Xor,
+    /// Only board cells that are also alive in the pattern survive.
+    And,
+    /// The pattern's footprint overwrites the board, dead cells included.
+    Replace,
+    /// Pattern cells kill the board cells underneath them.
+    Erase,
+}
+
+impl StampMode {
+    pub fn from_name(name: &str) -> Option<StampMode> {
+        match name {
+            "or" => Some(StampMode::Or),
+            "xor" => Some(StampMode::Xor),
+            "and" => Some(StampMode::And),
+            "replace" => Some(StampMode::Replace),
+            "erase" => Some(StampMode::Erase),
+            _ => None,
+        }
+    }
+
+    fn apply(self, board: Cell, pattern: Cell) -> Cell {
+        match (self, board, pattern) {
+            (StampMode::Or, Cell::Alive, _) | (StampMode::Or, _, Cell::Alive) => Cell::Alive,
+            (StampMode::Xor, a, b) if a != b => Cell::Alive,
+            (StampMode::And, Cell::Alive, Cell::Alive) => Cell::Alive,
+            (StampMode::Replace, _, pattern) => pattern,
+            (StampMode::Erase, board, Cell::Dead) => board,
+            _ => Cell::Dead,
+        }
+    }
+}
+
+/// A rectangle of cells, such as a selection on the board.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Region {
+    pub row: u32,
+    pub column: u32,
+    pub height: u32,
+    pub width: u32,
+}
+
+impl Region {
+    /// The smallest region holding both (row, column) corners.
+    pub fn spanning(a: (u32, u32), b: (u32, u32)) -> Region {
+        Region {
+            row: a.0.min(b.0),
+            column: a.1.min(b.1),
+            height: a.0.max(b.0) - a.0.min(b.0) + 1,
+            width: a.1.max(b.1) - a.1.min(b.1) + 1,
+        }
+    }
+}
+
+/// The pair of characters a universe is drawn with as text.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Charset {
+    pub dead: char,
+    pub alive: char,
+}
 
+impl Charset {
+    /// What `Display` prints.
+    pub const SQUARES: Charset = Charset {
+        dead: '◻',
+        alive: '◼',
+    };
+    pub const DOTS: Charset = Charset {
+        dead: '.',
+        alive: 'O',
+    };
+    pub const DASHES: Charset = Charset {
+        dead: '-',
+        alive: '#',
+    };
+    pub const SPACES: Charset = Charset {
+        dead: ' ',
+        alive: '*',
+    };
+
+    /// Every charset `FromStr` recognizes, in the order it tries them.
+    pub const ALL: &'static [Charset] = &[
+        Charset::SQUARES,
+        Charset::DOTS,
+        Charset::DASHES,
+        Charset::SPACES,
+    ];
+
+    /// The first of `ALL` that can draw every character in `text`.
+    pub fn detect(text: &str) -> Option<Charset> {
+        Charset::ALL.iter().cloned().find(|charset| {
+            text.lines()
+                .flat_map(str::chars)
+                .all(|ch| ch == charset.dead || ch == charset.alive)
+        })
+    }
+
+    fn symbol(self, cell: Cell) -> char {
+        if cell == Cell::Dead {
+            self.dead
+        } else {
+            self.alive
+        }
+    }
+}
+
+impl Default for Charset {
+    fn default() -> Charset {
+        Charset::SQUARES
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Universe {
     width: u32,
     height: u32,
     cells: Vec<Cell>,
+    generation: u64,
 }
 
 impl Universe {
@@ -72,6 +196,23 @@ impl Universe {
     pub fn cells(&self) -> &Vec<Cell> {
         &self.cells
     }
+
+    /// Number of live cells.
+    pub fn population(&self) -> u64 {
+        self.cells
+            .iter()
+            .filter(|&&cell| cell == Cell::Alive)
+            .count() as u64
+    }
+
+    /// Number of `tick`s since the universe was created.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub fn set_generation(&mut self, generation: u64) {
+        self.generation = generation;
+    }
     pub fn tick(&mut self) {
         let mut next = self.cells.clone();
 
@@ -93,6 +234,7 @@ impl Universe {
         }
 
         self.cells = next;
+        self.generation += 1;
     }
 
     pub fn clear(&mut self) {
@@ -117,24 +259,24 @@ impl Universe {
             width,
             height,
             cells,
+            generation: 0,
         }
     }
 
-    // Could impl From, but keeping it simple for now
-    pub fn from(html_elem: web_sys::HtmlElement) -> Universe {
-        let data = html_elem.dataset();
-        let height = data.get("height").unwrap().parse::<u32>().unwrap();
-        let width = data.get("width").unwrap().parse::<u32>().unwrap();
-        let pattern = data.get("pattern").unwrap();
-        let cells = pattern
-            .chars()
-            .map(|char| if char == '1' { Cell::Alive } else { Cell::Dead })
-            .collect();
-
+    pub fn empty(width: u32, height: u32) -> Universe {
         Universe {
             width,
             height,
-            cells,
+            cells: vec![Cell::Dead; (width * height) as usize],
+            generation: 0,
+        }
+    }
+
+    /// Sets the given (row, column) cells alive.
+    pub fn set_cells(&mut self, cells: &[(u32, u32)]) {
+        for (row, col) in cells.iter().cloned() {
+            let idx = self.get_index(row, col);
+            self.cells[idx] = Cell::Alive;
         }
     }
 
@@ -142,18 +284,265 @@ impl Universe {
         let idx = self.get_index(row, column);
         self.cells[idx].toggle();
     }
+
+    /// Combines `pattern` into the board with its top left corner at
+    /// (`row`, `column`). Positions are taken modulo the board size, so
+    /// patterns crossing an edge wrap around the torus. Does nothing when
+    /// either the board or the pattern is empty.
+    pub fn stamp(&mut self, pattern: &Universe, row: i32, column: i32, mode: StampMode) {
+        if self.cells.is_empty() || pattern.cells.is_empty() {
+            return;
+        }
+        for pattern_row in 0..pattern.height() {
+            for pattern_col in 0..pattern.width() {
+                let target_row = (row + pattern_row as i32).rem_euclid(self.height as i32) as u32;
+                let target_col = (column + pattern_col as i32).rem_euclid(self.width as i32) as u32;
+                let idx = self.get_index(target_row, target_col);
+                let pattern_cell = pattern.cells[pattern.get_index(pattern_row, pattern_col)];
+                self.cells[idx] = mode.apply(self.cells[idx], pattern_cell);
+            }
+        }
+    }
 }
 
-impl fmt::Display for Universe {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        for line in self.cells.as_slice().chunks(self.width as usize) {
-            for &cell in line {
-                let symbol = if cell == Cell::Dead { '◻' } else { '◼' };
-                write!(f, "{}", symbol)?;
+/// Transformations. Each returns a new universe and leaves `self` untouched.
+impl Universe {
+    // Builds a `width` x `height` universe whose cell at (row, column) is the
+    // cell of `self` at `source(row, column)`.
+    fn remap<F>(&self, width: u32, height: u32, source: F) -> Universe
+    where
+        F: Fn(u32, u32) -> (u32, u32),
+    {
+        let mut cells = Vec::with_capacity((width * height) as usize);
+        for row in 0..height {
+            for col in 0..width {
+                let (src_row, src_col) = source(row, col);
+                cells.push(self.cells[self.get_index(src_row, src_col)]);
+            }
+        }
+
+        Universe {
+            width,
+            height,
+            cells,
+            generation: 0,
+        }
+    }
+
+    /// Rotates a quarter turn clockwise.
+    pub fn rotate_90(&self) -> Universe {
+        let height = self.height;
+        self.remap(self.height, self.width, |row, col| (height - 1 - col, row))
+    }
+
+    pub fn rotate_180(&self) -> Universe {
+        let (width, height) = (self.width, self.height);
+        self.remap(width, height, |row, col| {
+            (height - 1 - row, width - 1 - col)
+        })
+    }
+
+    /// Rotates a quarter turn counter-clockwise.
+    pub fn rotate_270(&self) -> Universe {
+        let width = self.width;
+        self.remap(self.height, self.width, |row, col| (col, width - 1 - row))
+    }
+
+    /// Mirrors left to right.
+    pub fn flip_horizontal(&self) -> Universe {
+        let width = self.width;
+        self.remap(self.width, self.height, |row, col| (row, width - 1 - col))
+    }
+
+    /// Mirrors top to bottom.
+    pub fn flip_vertical(&self) -> Universe {
+        let height = self.height;
+        self.remap(self.width, self.height, |row, col| (height - 1 - row, col))
+    }
+
+    /// Mirrors along the main diagonal, swapping rows and columns.
+    pub fn transpose(&self) -> Universe {
+        self.remap(self.height, self.width, |row, col| (col, row))
+    }
+
+    /// Returns the smallest (row, column, height, width) rectangle holding
+    /// every live cell, or `None` when the universe is empty.
+    pub fn bounding_box(&self) -> Option<(u32, u32, u32, u32)> {
+        let mut bounds: Option<(u32, u32, u32, u32)> = None;
+        for row in 0..self.height {
+            for col in 0..self.width {
+                if self.cells[self.get_index(row, col)] == Cell::Dead {
+                    continue;
+                }
+                bounds = Some(match bounds {
+                    None => (row, col, row, col),
+                    Some((top, left, bottom, right)) => {
+                        (top, left.min(col), bottom.max(row), right.max(col))
+                    }
+                });
+            }
+        }
+
+        bounds.map(|(top, left, bottom, right)| (top, left, bottom - top + 1, right - left + 1))
+    }
+
+    /// The live bounding box as a region.
+    pub fn bounding_region(&self) -> Option<Region> {
+        self.bounding_box()
+            .map(|(row, column, height, width)| Region {
+                row,
+                column,
+                height,
+                width,
+            })
+    }
+
+    /// Crops to the live bounding box. An empty universe crops to 0x0.
+    pub fn crop(&self) -> Universe {
+        match self.bounding_region() {
+            Some(bounds) => self.copy_region(bounds),
+            None => Universe::empty(0, 0),
+        }
+    }
+}
+
+/// Region editing. Regions wrap around the edges like the board does.
+impl Universe {
+    fn region_indices(&self, region: Region) -> Vec<usize> {
+        let mut indices = Vec::with_capacity((region.height * region.width) as usize);
+        for row in region.row..region.row + region.height {
+            for col in region.column..region.column + region.width {
+                indices.push(self.get_index(row % self.height, col % self.width));
+            }
+        }
+        indices
+    }
+
+    /// Copies the cells inside `region` into a new universe of its size.
+    pub fn copy_region(&self, region: Region) -> Universe {
+        let cells = self
+            .region_indices(region)
+            .into_iter()
+            .map(|idx| self.cells[idx])
+            .collect();
+
+        Universe {
+            width: region.width,
+            height: region.height,
+            cells,
+            generation: 0,
+        }
+    }
+
+    pub fn fill_region(&mut self, region: Region, cell: Cell) {
+        for idx in self.region_indices(region) {
+            self.cells[idx] = cell;
+        }
+    }
+
+    pub fn invert_region(&mut self, region: Region) {
+        for idx in self.region_indices(region) {
+            self.cells[idx].toggle();
+        }
+    }
+
+    /// Sets each cell in `region` alive whenever `alive` returns true.
+    pub fn randomize_region<F>(&mut self, region: Region, mut alive: F)
+    where
+        F: FnMut() -> bool,
+    {
+        for idx in self.region_indices(region) {
+            self.cells[idx] = if alive() { Cell::Alive } else { Cell::Dead };
+        }
+    }
+}
+
+impl Default for Universe {
+    fn default() -> Universe {
+        Universe::new()
+    }
+}
+
+/// Text drawings, one line per row.
+impl Universe {
+    pub fn to_text(&self, charset: Charset) -> String {
+        let mut text = String::with_capacity(self.cells.len() + self.height as usize);
+        for row in 0..self.height {
+            for col in 0..self.width {
+                text.push(charset.symbol(self.cells[self.get_index(row, col)]));
+            }
+            text.push('\n');
+        }
+        text
+    }
+
+    /// Reads a drawing made with `charset`. Every row must be the same
+    /// width; blank text is a 0x0 universe.
+    pub fn from_text(text: &str, charset: Charset) -> Result<Universe, ParseError> {
+        let mut width = None;
+        let mut cells = vec![];
+        let mut height = 0;
+        for (line_idx, line) in text.lines().enumerate() {
+            let mut row_width = 0u32;
+            for (col, ch) in line.chars().enumerate() {
+                cells.push(match ch {
+                    ch if ch == charset.dead => Cell::Dead,
+                    ch if ch == charset.alive => Cell::Alive,
+                    ch => {
+                        return Err(ParseError::new(
+                            line_idx + 1,
+                            col + 1,
+                            format!("unexpected character {:?}", ch),
+                        ))
+                    }
+                });
+                row_width += 1;
+            }
+            match width {
+                None => width = Some(row_width),
+                Some(width) if width != row_width => {
+                    return Err(ParseError::new(
+                        line_idx + 1,
+                        width.min(row_width) as usize + 1,
+                        format!("row is {} cells wide, expected {}", row_width, width),
+                    ))
+                }
+                _ => {}
             }
-            write!(f, "\n")?;
+            height += 1;
         }
 
-        Ok(())
+        Ok(Universe {
+            width: width.unwrap_or(0),
+            height,
+            cells,
+            generation: 0,
+        })
+    }
+}
+
+impl fmt::Display for Universe {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.to_text(Charset::default()))
+    }
+}
+
+/// Parses `Display` output, or a drawing in any of `Charset::ALL`.
+impl FromStr for Universe {
+    type Err = ParseError;
+
+    fn from_str(text: &str) -> Result<Universe, ParseError> {
+        // Without an exact match, errors are reported against the charset
+        // of the first cell so they point at the stray character
+        let charset = Charset::detect(text)
+            .or_else(|| {
+                let first = text.lines().flat_map(str::chars).next()?;
+                Charset::ALL
+                    .iter()
+                    .cloned()
+                    .find(|charset| first == charset.dead || first == charset.alive)
+            })
+            .unwrap_or_default();
+        Universe::from_text(text, charset)
     }
 }
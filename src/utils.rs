@@ -26,13 +26,7 @@ pub fn document() -> web_sys::Document {
 pub fn element_by_id(id: &str) -> web_sys::Element {
     document()
         .get_element_by_id(id)
-        .expect(format!("Should have {} on `document`", id).as_str())
-}
-
-pub fn html_element_by_id(id: &str) -> web_sys::HtmlElement {
-    element_by_id(id)
-        .dyn_into::<web_sys::HtmlElement>()
-        .expect("Should have `HtmlElement` on `document`")
+        .unwrap_or_else(|| panic!("Should have {} on `document`", id))
 }
 
 pub fn performance() -> web_sys::Performance {
@@ -52,3 +46,21 @@ pub fn cancel_animation_frame(handle: i32) {
         .cancel_animation_frame(handle)
         .expect("Should cancel animation frame");
 }
+
+/// Offers `bytes` to the user as a file called `name`.
+pub fn download(name: &str, bytes: &[u8], mime_type: &str) -> Result<(), JsValue> {
+    let parts = js_sys::Array::of1(&js_sys::Uint8Array::from(bytes));
+    let options = web_sys::BlobPropertyBag::new();
+    options.set_type(mime_type);
+    let blob = web_sys::Blob::new_with_u8_array_sequence_and_options(&parts, &options)?;
+    let url = web_sys::Url::create_object_url_with_blob(&blob)?;
+
+    let anchor = document()
+        .create_element("a")?
+        .dyn_into::<web_sys::HtmlAnchorElement>()?;
+    anchor.set_href(&url);
+    anchor.set_download(name);
+    anchor.click();
+
+    web_sys::Url::revoke_object_url(&url)
+}
@@ -0,0 +1,87 @@
+//! Native tests for apgcode encoding and decoding.
+
+extern crate wasm_game_of_life;
+
+use wasm_game_of_life::apgcode;
+use wasm_game_of_life::formats::ParseError;
+use wasm_game_of_life::universe::Universe;
+
+fn pattern(width: u32, height: u32, cells: &[(u32, u32)]) -> Universe {
+    let mut universe = Universe::empty(width, height);
+    universe.set_cells(cells);
+    universe
+}
+
+#[test]
+fn encodes_well_known_objects() {
+    let block = pattern(2, 2, &[(0, 0), (0, 1), (1, 0), (1, 1)]);
+    let beehive = pattern(4, 3, &[(0, 1), (0, 2), (1, 0), (1, 3), (2, 1), (2, 2)]);
+    let boat = pattern(3, 3, &[(0, 0), (0, 1), (1, 0), (1, 2), (2, 1)]);
+    let blinker = pattern(3, 1, &[(0, 0), (0, 1), (0, 2)]);
+    let toad = pattern(4, 2, &[(0, 1), (0, 2), (0, 3), (1, 0), (1, 1), (1, 2)]);
+    let glider = pattern(3, 3, &[(0, 1), (1, 2), (2, 0), (2, 1), (2, 2)]);
+    let lwss = pattern(
+        5,
+        4,
+        &[
+            (0, 1),
+            (0, 4),
+            (1, 0),
+            (2, 0),
+            (2, 4),
+            (3, 0),
+            (3, 1),
+            (3, 2),
+            (3, 3),
+        ],
+    );
+
+    assert_eq!(apgcode::encode(&block).unwrap(), "xs4_33");
+    assert_eq!(apgcode::encode(&beehive).unwrap(), "xs6_696");
+    assert_eq!(apgcode::encode(&boat).unwrap(), "xs5_253");
+    assert_eq!(apgcode::encode(&blinker).unwrap(), "xp2_7");
+    assert_eq!(apgcode::encode(&toad).unwrap(), "xp2_7e");
+    assert_eq!(apgcode::encode(&glider).unwrap(), "xq4_153");
+    assert_eq!(apgcode::encode(&lwss).unwrap(), "xq4_6frc");
+    assert_eq!(apgcode::encode(&Universe::empty(3, 3)).unwrap(), "xs0_0");
+    // The R-pentomino takes over a thousand generations to settle
+    let r_pentomino = pattern(3, 3, &[(0, 1), (0, 2), (1, 0), (1, 1), (2, 1)]);
+    assert_eq!(apgcode::encode(&r_pentomino), None);
+}
+
+#[test]
+fn decodes_back_to_canonical_phase() {
+    for code in [
+        "xs4_33", "xs6_696", "xp2_7e", "xq4_153", "xq4_6frc", "xs8_6996",
+    ]
+    .iter()
+    {
+        let object = apgcode::decode(code).unwrap();
+        assert_eq!(apgcode::encode(&object).as_deref(), Some(*code));
+    }
+
+    let spaced = apgcode::decode("xs4_1y21").unwrap();
+    assert_eq!((spaced.width(), spaced.height()), (8, 1));
+    let stacked = apgcode::decode("xp2_1z1").unwrap();
+    assert_eq!((stacked.width(), stacked.height()), (1, 6));
+
+    assert_eq!(apgcode::decode("xs4_3!").unwrap_err().column, 6);
+    assert!(apgcode::decode("yl144_1").is_err());
+    assert!(apgcode::decode("xs4").is_err());
+}
+
+#[test]
+fn decode_rejects_codes_too_large_to_build() {
+    let wide = format!("xs1_{}1", "yz".repeat(2_000_000));
+    assert_eq!(
+        apgcode::decode(&wide).unwrap_err().message,
+        "pattern is too large"
+    );
+
+    // Each side fits, but the two cells span too many between them
+    let spread = format!("xs2_{}1{}1", "yz".repeat(10_000), "z".repeat(10_000));
+    assert_eq!(
+        apgcode::decode(&spread).unwrap_err(),
+        ParseError::new(1, 30006, "pattern is too large")
+    );
+}
@@ -0,0 +1,140 @@
+//! Native tests for the object census.
+
+extern crate wasm_game_of_life;
+
+use wasm_game_of_life::census::{self, Census, CensusEntry};
+use wasm_game_of_life::universe::{Region, StampMode, Universe};
+use wasm_game_of_life::{apgcode, library};
+
+#[test]
+fn separate_objects_are_counted_and_named() {
+    let mut universe = Universe::empty(20, 12);
+    universe.set_cells(&[
+        // Two blocks
+        (1, 1),
+        (1, 2),
+        (2, 1),
+        (2, 2),
+        (1, 10),
+        (1, 11),
+        (2, 10),
+        (2, 11),
+        // A blinker
+        (8, 3),
+        (8, 4),
+        (8, 5),
+        // A glider
+        (6, 14),
+        (7, 15),
+        (8, 13),
+        (8, 14),
+        (8, 15),
+    ]);
+
+    let census = universe.census();
+    assert_eq!(census.objects.len(), 4);
+    assert_eq!(
+        census.objects[0].region,
+        Region {
+            row: 1,
+            column: 1,
+            height: 2,
+            width: 2
+        }
+    );
+    assert_eq!(
+        census.entries(),
+        vec![
+            CensusEntry {
+                apgcode: Some(String::from("xs4_33")),
+                name: Some("block"),
+                count: 2,
+            },
+            CensusEntry {
+                apgcode: Some(String::from("xp2_7")),
+                name: Some("blinker"),
+                count: 1,
+            },
+            CensusEntry {
+                apgcode: Some(String::from("xq4_153")),
+                name: Some("glider"),
+                count: 1,
+            },
+        ]
+    );
+    assert_eq!(
+        census.report(),
+        "count\tapgcode\tname\n2\txs4_33\tblock\n1\txp2_7\tblinker\n1\txq4_153\tglider\n"
+    );
+}
+
+#[test]
+fn nearby_cells_are_grouped_into_one_object() {
+    // Blocks one cell apart make a single bi-block pseudo-object
+    let mut universe = Universe::empty(12, 12);
+    universe.set_cells(&[
+        (1, 1),
+        (1, 2),
+        (2, 1),
+        (2, 2),
+        (1, 4),
+        (1, 5),
+        (2, 4),
+        (2, 5),
+    ]);
+    let census = universe.census();
+    assert_eq!(census.objects.len(), 1);
+    assert_eq!(census.objects[0].population, 8);
+    assert_eq!(census.objects[0].name, None);
+}
+
+#[test]
+fn objects_crossing_the_edges_stay_whole() {
+    let mut universe = Universe::empty(10, 10);
+    // A block split across all four corners
+    universe.set_cells(&[(0, 0), (0, 9), (9, 0), (9, 9)]);
+    let census = universe.census();
+    assert_eq!(census.objects.len(), 1);
+    assert_eq!(census.objects[0].name, Some("block"));
+    assert_eq!(
+        census.objects[0].region,
+        Region {
+            row: 9,
+            column: 9,
+            height: 2,
+            width: 2
+        }
+    );
+}
+
+#[test]
+fn unsettled_objects_have_no_apgcode() {
+    let r_pentomino = library::get("r-pentomino").unwrap().universe();
+    let mut universe = Universe::empty(10, 10);
+    universe.stamp(&r_pentomino, 3, 3, StampMode::Or);
+    let census = universe.census();
+    assert_eq!(census.objects[0].apgcode, None);
+    assert_eq!(census.report(), "count\tapgcode\tname\n1\t-\t\n");
+    assert_eq!(Census::take(&Universe::empty(4, 4)), Census::default());
+}
+
+#[test]
+fn known_objects_are_canonical_and_cover_the_library() {
+    for &(code, _) in census::KNOWN_OBJECTS.iter() {
+        let object = apgcode::decode(code).unwrap();
+        assert_eq!(apgcode::encode(&object).as_deref(), Some(code));
+    }
+    for key in [
+        "block", "beehive", "blinker", "toad", "pulsar", "glider", "lwss",
+    ]
+    .iter()
+    {
+        let code = apgcode::encode(&library::get(key).unwrap().universe()).unwrap();
+        assert!(
+            census::name(&code).is_some(),
+            "{} ({}) has no name",
+            key,
+            code
+        );
+    }
+}
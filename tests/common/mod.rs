@@ -0,0 +1,24 @@
+//! Boards shared by the native test files, included with `mod common;`.
+
+// Each test file is its own crate and uses only some of these
+#![allow(dead_code)]
+
+use wasm_game_of_life::universe::Universe;
+
+/// The glider on a 3x3 board:
+///
+/// ```text
+/// .O.
+/// ..O
+/// OOO
+/// ```
+pub fn glider() -> Universe {
+    glider_on(3, 3)
+}
+
+/// The glider in the top left corner of a `width` x `height` board.
+pub fn glider_on(width: u32, height: u32) -> Universe {
+    let mut universe = Universe::empty(width, height);
+    universe.set_cells(&[(0, 1), (1, 2), (2, 0), (2, 1), (2, 2)]);
+    universe
+}
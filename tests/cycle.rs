@@ -0,0 +1,130 @@
+//! Native tests for finding repeating boards.
+
+extern crate wasm_game_of_life;
+
+mod common;
+
+use common::glider_on;
+use wasm_game_of_life::cycle::{Cycle, CycleDetector, CycleKind, Velocity};
+use wasm_game_of_life::library;
+use wasm_game_of_life::universe::{StampMode, Universe};
+
+// Ticks until the detector reports a cycle, giving up after `limit` ticks
+fn run_until_cycle(
+    universe: &mut Universe,
+    detector: &mut CycleDetector,
+    limit: u32,
+) -> Option<Cycle> {
+    if let Some(cycle) = detector.observe(universe) {
+        return Some(cycle);
+    }
+    for _ in 0..limit {
+        universe.tick();
+        if let Some(cycle) = detector.observe(universe) {
+            return Some(cycle);
+        }
+    }
+    None
+}
+
+#[test]
+fn blinker_is_a_period_2_oscillator() {
+    let mut universe = Universe::empty(5, 5);
+    universe.set_cells(&[(2, 1), (2, 2), (2, 3)]);
+    let cycle = run_until_cycle(&mut universe, &mut CycleDetector::default(), 10).unwrap();
+    assert_eq!(cycle.start, 0);
+    assert_eq!(cycle.period, 2);
+    assert_eq!(cycle.kind(), CycleKind::Oscillator);
+    assert_eq!(cycle.to_string(), "period-2 oscillator");
+    assert_eq!(universe.generation(), 2);
+}
+
+#[test]
+fn still_lives_and_dead_boards_have_period_1() {
+    let mut block = Universe::empty(6, 6);
+    block.set_cells(&[(1, 1), (1, 2), (2, 1), (2, 2)]);
+    let cycle = run_until_cycle(&mut block, &mut CycleDetector::default(), 10).unwrap();
+    assert_eq!((cycle.start, cycle.period), (0, 1));
+    assert_eq!(cycle.to_string(), "still life");
+
+    // A lone pair of cells dies after one generation
+    let mut pair = Universe::empty(6, 6);
+    pair.set_cells(&[(1, 1), (1, 2)]);
+    let cycle = run_until_cycle(&mut pair, &mut CycleDetector::default(), 10).unwrap();
+    assert_eq!((cycle.start, cycle.period), (1, 1));
+    assert_eq!(cycle.kind(), CycleKind::Dead);
+    assert_eq!(cycle.to_string(), "dead");
+}
+
+#[test]
+fn glider_is_a_c4_diagonal_spaceship() {
+    let mut universe = glider_on(8, 8);
+    let cycle = run_until_cycle(&mut universe, &mut CycleDetector::default(), 100).unwrap();
+    assert_eq!((cycle.start, cycle.period), (0, 4));
+    assert_eq!(cycle.displacement, (1, 1));
+    assert_eq!(cycle.kind(), CycleKind::Spaceship);
+    assert_eq!(cycle.to_string(), "c/4 diagonal spaceship");
+    assert_eq!(cycle.velocity().unwrap().heading(), "south-east");
+}
+
+#[test]
+fn spaceships_are_tracked_across_the_edges() {
+    // A glider flying north-west, starting over the top left corner
+    let glider = library::get("glider").unwrap().universe().rotate_180();
+    let mut universe = Universe::empty(12, 10);
+    universe.stamp(&glider, -1, -1, StampMode::Or);
+    let cycle = run_until_cycle(&mut universe, &mut CycleDetector::default(), 100).unwrap();
+    assert_eq!(cycle.displacement, (-1, -1));
+    assert_eq!(cycle.velocity().unwrap().heading(), "north-west");
+
+    let lwss = library::get("lwss").unwrap().universe();
+    let mut universe = Universe::empty(20, 12);
+    universe.stamp(&lwss, 4, 16, StampMode::Or);
+    let cycle = run_until_cycle(&mut universe, &mut CycleDetector::default(), 100).unwrap();
+    assert_eq!(cycle.period, 4);
+    assert_eq!(cycle.displacement.1, 0);
+    assert_eq!(cycle.displacement.0.abs(), 2);
+    assert_eq!(cycle.to_string(), "c/2 orthogonal spaceship");
+}
+
+#[test]
+fn velocities_use_the_usual_notation() {
+    let velocity = |dx, dy, period| Velocity { dx, dy, period };
+    assert_eq!(velocity(0, -2, 5).to_string(), "2c/5 orthogonal");
+    assert_eq!(velocity(0, -2, 5).heading(), "north");
+    assert_eq!(velocity(-3, 0, 3).to_string(), "c orthogonal");
+    assert_eq!(velocity(2, -2, 8).to_string(), "c/4 diagonal");
+    assert_eq!(velocity(2, 1, 6).to_string(), "(2,1)c/6 oblique");
+    assert_eq!(velocity(2, -1, 6).heading(), "east-north-east");
+    assert_eq!(velocity(-1, 2, 6).heading(), "south-south-west");
+    assert_eq!(velocity(4, 2, 12).to_string(), "(2,1)c/6 oblique");
+}
+
+#[test]
+fn cycle_is_reported_once_until_the_board_is_edited() {
+    let mut universe = Universe::empty(5, 5);
+    universe.set_cells(&[(2, 1), (2, 2), (2, 3)]);
+    let mut detector = CycleDetector::default();
+    assert!(run_until_cycle(&mut universe, &mut detector, 10).is_some());
+    assert!(run_until_cycle(&mut universe, &mut detector, 10).is_none());
+    assert_eq!(detector.cycle().map(|cycle| cycle.period), Some(2));
+
+    // Killing the blinker mid-run starts the history over
+    universe.clear();
+    assert_eq!(detector.observe(&universe), None);
+    assert_eq!(detector.cycle(), None);
+    universe.tick();
+    let cycle = detector.observe(&universe).unwrap();
+    assert_eq!(cycle.kind(), CycleKind::Dead);
+    assert_eq!(cycle.start, universe.generation() - 1);
+}
+
+#[test]
+fn periods_longer_than_the_history_are_missed() {
+    let mut universe = Universe::empty(5, 5);
+    universe.set_cells(&[(2, 1), (2, 2), (2, 3)]);
+    assert_eq!(
+        run_until_cycle(&mut universe, &mut CycleDetector::new(1), 10),
+        None
+    );
+}
@@ -0,0 +1,185 @@
+//! Native tests for rendering boards to images.
+
+extern crate wasm_game_of_life;
+
+mod common;
+
+use common::glider;
+use wasm_game_of_life::checksum::adler32;
+use wasm_game_of_life::export::{self, png, RenderOptions, Rgba, RgbaImage};
+use wasm_game_of_life::image::ImageError;
+use wasm_game_of_life::universe::Universe;
+
+fn decode(bytes: &[u8]) -> RgbaImage {
+    let decoder = ::png::Decoder::new(bytes);
+    let mut reader = decoder.read_info().unwrap();
+    let mut pixels = vec![0; reader.output_buffer_size()];
+    let frame = reader.next_frame(&mut pixels).unwrap();
+    assert_eq!(frame.color_type, ::png::ColorType::Rgba);
+    RgbaImage {
+        width: frame.width,
+        height: frame.height,
+        pixels,
+    }
+}
+
+#[test]
+fn adler32_matches_the_reference_value() {
+    assert_eq!(adler32(b"Wikipedia"), 0x11E6_0398);
+    assert_eq!(adler32(b""), 1);
+}
+
+#[test]
+fn rgba_parses_hex_colors() {
+    assert_eq!(Rgba::from_hex("#CCCCCC"), Some(Rgba::opaque(204, 204, 204)));
+    assert_eq!(
+        Rgba::from_hex("#0078ff4c"),
+        Some(Rgba {
+            r: 0,
+            g: 120,
+            b: 255,
+            a: 76
+        })
+    );
+    assert_eq!(Rgba::from_hex("CCCCCC"), None);
+    assert_eq!(Rgba::from_hex("#CCC"), None);
+}
+
+#[test]
+fn render_matches_the_canvas_layout() {
+    let options = RenderOptions::default();
+    let image = export::render(&glider(), &options).unwrap();
+
+    // Grid lines at every multiple of the pitch, then a cell
+    assert_eq!((image.width, image.height), (34, 34));
+    assert_eq!(image.get(0, 5), options.grid_color);
+    assert_eq!(image.get(11, 5), options.grid_color);
+    assert_eq!(image.get(1, 1), options.dead_color);
+    assert_eq!(image.get(12, 1), options.alive_color);
+    assert_eq!(image.get(21, 10), options.alive_color);
+
+    let plain = RenderOptions {
+        cell_size: 1,
+        grid: false,
+        ..RenderOptions::default()
+    };
+    let image = export::render(&glider(), &plain).unwrap();
+    assert_eq!((image.width, image.height), (3, 3));
+    assert_eq!(image.get(1, 0), plain.alive_color);
+    assert_eq!(image.get(0, 0), plain.dead_color);
+}
+
+#[test]
+fn png_round_trips_through_a_decoder() {
+    let mut universe = Universe::new();
+    universe.tick();
+    let image = export::render(&universe, &RenderOptions::default()).unwrap();
+    let bytes = png::encode(&image);
+
+    assert!(bytes.starts_with(png::SIGNATURE));
+    assert_eq!(decode(&bytes), image);
+    // Runs compress well, far below four bytes a pixel
+    assert!(bytes.len() < image.pixels.len() / 10);
+
+    // Noise exercises every filter and literal
+    let pixels = (0..97u32 * 13 * 4)
+        .map(|idx| (idx.wrapping_mul(2_654_435_761) >> 13) as u8)
+        .collect();
+    let noise = RgbaImage {
+        width: 97,
+        height: 13,
+        pixels,
+    };
+    assert_eq!(decode(&png::encode(&noise)), noise);
+}
+
+#[test]
+fn universe_to_png_uses_the_options() {
+    let options = RenderOptions {
+        cell_size: 4,
+        grid: false,
+        alive_color: Rgba::opaque(255, 0, 0),
+        ..RenderOptions::default()
+    };
+    let image = decode(&glider().to_png(&options).unwrap());
+    assert_eq!((image.width, image.height), (12, 12));
+    assert_eq!(image.get(5, 2), Rgba::opaque(255, 0, 0));
+}
+
+#[test]
+fn render_rejects_images_too_large_to_hold() {
+    let result = export::render(&Universe::empty(8000, 8000), &RenderOptions::default());
+    assert_eq!(
+        result,
+        Err(ImageError::TooLarge {
+            width: 88001,
+            height: 88001
+        })
+    );
+
+    // Sizes past u32 saturate rather than wrapping around
+    let huge = RenderOptions {
+        cell_size: u32::MAX,
+        ..RenderOptions::default()
+    };
+    assert_eq!(
+        glider().to_png(&huge),
+        Err(ImageError::TooLarge {
+            width: u32::MAX,
+            height: u32::MAX
+        })
+    );
+}
+
+#[test]
+fn svg_merges_cells_into_rectangles() {
+    let options = RenderOptions {
+        cell_size: 4,
+        grid: false,
+        ..RenderOptions::default()
+    };
+
+    // A block is one rectangle
+    let mut block = Universe::empty(4, 4);
+    block.set_cells(&[(1, 1), (1, 2), (2, 1), (2, 2)]);
+    let svg = block.to_svg(&options);
+    assert_eq!(svg.matches("<rect").count(), 2);
+    assert!(svg.contains(r#"<rect x="4" y="4" width="8" height="8"/>"#));
+
+    // The glider's bottom row is a run, the rest single cells
+    let svg = glider().to_svg(&options);
+    assert!(svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="12" height="12""#));
+    assert!(svg.contains(r#"<rect x="4" y="0" width="4" height="4"/>"#));
+    assert!(svg.contains(r#"<rect x="8" y="4" width="4" height="4"/>"#));
+    assert!(svg.contains(r#"<rect x="0" y="8" width="12" height="4"/>"#));
+    assert!(!svg.contains("<line"));
+}
+
+#[test]
+fn svg_draws_the_grid_and_colors() {
+    let options = RenderOptions {
+        alive_color: Rgba::opaque(255, 0, 0),
+        dead_color: Rgba {
+            r: 255,
+            g: 255,
+            b: 255,
+            a: 0,
+        },
+        ..RenderOptions::default()
+    };
+    let mut blinker = Universe::empty(3, 3);
+    blinker.set_cells(&[(0, 1), (1, 1), (2, 1)]);
+    let svg = blinker.to_svg(&options);
+
+    // Same layout as the canvas, one rectangle spanning the grid lines
+    assert!(svg.contains(r#"width="34" height="34""#));
+    assert!(svg.contains(r#"<rect x="12" y="1" width="10" height="32"/>"#));
+    assert!(svg.contains(r##"<g fill="#ff0000">"##));
+    assert!(svg.contains(r##"fill="#ffffff" fill-opacity="0.000""##));
+    assert!(svg.contains(
+        r##"<line x1="17" y1="0" x2="17" y2="34" stroke-width="34" stroke="#cccccc" stroke-dasharray="1,10"/>"##
+    ));
+    assert!(svg.contains(
+        r##"<line x1="0" y1="17" x2="34" y2="17" stroke-width="34" stroke="#cccccc" stroke-dasharray="1,10"/>"##
+    ));
+}
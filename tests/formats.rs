@@ -0,0 +1,221 @@
+//! Native tests for the pattern file readers and writers.
+
+extern crate wasm_game_of_life;
+
+mod common;
+
+use common::glider;
+use wasm_game_of_life::formats::macrocell::{self, MacrocellPattern};
+use wasm_game_of_life::formats::{self, life105, life106, plaintext, rle, ParseError, Pattern};
+use wasm_game_of_life::quadtree::QuadTree;
+use wasm_game_of_life::universe::{StampMode, Universe};
+
+#[test]
+fn rle_reads_header_comments_and_runs() {
+    let pattern = rle::parse(
+        "#N Glider\n\
+         #C The smallest spaceship.\n\
+         x = 3, y = 3, rule = B3/S23\n\
+         bo$2b\n\
+         o$3o!\n\
+         trailing text is ignored",
+    )
+    .unwrap();
+
+    assert_eq!(pattern.name.as_deref(), Some("Glider"));
+    assert_eq!(pattern.rule.as_deref(), Some("B3/S23"));
+    assert_eq!(pattern.comments, vec!["The smallest spaceship."]);
+    assert_eq!(pattern.universe, glider());
+}
+
+#[test]
+fn rle_reads_rules_with_bounded_grid_suffixes() {
+    let pattern = rle::parse("x = 3, y = 3, rule = B3/S23:T64,64\nbo$2bo$3o!\n").unwrap();
+    assert_eq!(pattern.rule.as_deref(), Some("B3/S23:T64,64"));
+    assert_eq!(pattern.universe, glider());
+}
+
+#[test]
+fn rle_round_trips_through_writer() {
+    let mut board = Universe::empty(80, 4);
+    board.set_cells(&[(0, 0), (3, 79)]);
+    for col in (0..80).step_by(2) {
+        board.set_cells(&[(1, col)]);
+    }
+
+    let mut pattern = Pattern::from(board);
+    pattern.name = Some(String::from("Stripes"));
+    pattern.rule = Some(String::from("B3/S23"));
+    let text = rle::write(&pattern);
+
+    assert!(text.starts_with("#N Stripes\nx = 80, y = 4, rule = B3/S23\n"));
+    assert!(text.lines().all(|line| line.len() <= 70));
+    assert_eq!(rle::parse(&text).unwrap(), pattern);
+    assert_eq!(
+        glider().to_rle(),
+        "x = 3, y = 3, rule = B3/S23\nbo$2bo$3o!\n"
+    );
+}
+
+#[test]
+fn rle_reports_malformed_input_position() {
+    assert_eq!(
+        rle::parse("x = 3, y = 3\nbo$2bq$3o!").unwrap_err(),
+        ParseError::new(2, 6, "unexpected character 'q'")
+    );
+    assert_eq!(
+        rle::parse("x = 2, y = 1\n3o!").unwrap_err(),
+        ParseError::new(2, 2, "live cells extend past the pattern size")
+    );
+    assert_eq!(rle::parse("x = a, y = 1\n").unwrap_err().column, 1);
+    assert_eq!(rle::parse("x = 1, y = 1\n12").unwrap_err().column, 1);
+}
+
+#[test]
+fn rle_rejects_huge_sizes_before_decoding_cells() {
+    let mut text = String::from("x = 4294967295, y = 1\n");
+    for _ in 0..64 {
+        text.push_str("67108864o");
+    }
+    assert_eq!(
+        rle::parse(&text).unwrap_err(),
+        ParseError::new(1, 1, "pattern of 4294967295x1 cells is too large")
+    );
+
+    // Without a header, wide runs on a far row mustn't overflow the check
+    let text = format!("{}67108864o!", "67108864$".repeat(4096));
+    assert_eq!(
+        rle::parse(&text).unwrap_err().message,
+        "live cells extend past the pattern size"
+    );
+}
+
+#[test]
+fn plaintext_reads_and_writes_cells_files() {
+    let pattern = plaintext::parse("!Name: Glider\n!A comment\n.O\n..O\nOOO\n\n").unwrap();
+    assert_eq!(pattern.name.as_deref(), Some("Glider"));
+    assert_eq!(pattern.comments, vec!["A comment"]);
+    assert_eq!(pattern.universe, glider());
+
+    assert_eq!(
+        plaintext::write(&pattern),
+        "!Name: Glider\n!A comment\n.O.\n..O\nOOO\n"
+    );
+    assert_eq!(
+        plaintext::parse("!\n.O.\n.X.").unwrap_err(),
+        ParseError::new(3, 2, "unexpected character 'X'")
+    );
+}
+
+#[test]
+fn life_106_reads_coordinates_relative_to_top_left() {
+    let pattern = life106::parse("#Life 1.06\n0 -1\n1 0\n-1 1\n0 1\n1 1\n").unwrap();
+    assert_eq!(pattern.universe, glider());
+    assert_eq!(
+        life106::write(&pattern),
+        "#Life 1.06\n1 0\n2 1\n0 2\n1 2\n2 2\n"
+    );
+    assert_eq!(
+        life106::parse("#Life 1.06\n0 1\n2 z").unwrap_err(),
+        ParseError::new(3, 3, "invalid coordinate 'z'")
+    );
+    assert_eq!(
+        life106::parse("#Life 1.06\n9223372036854775807 0\n-9223372036854775808 0\n")
+            .unwrap_err()
+            .message,
+        "pattern of 18446744073709551616x1 cells is too large"
+    );
+}
+
+#[test]
+fn life_105_reads_blocks_and_rules() {
+    let pattern =
+        life105::parse("#Life 1.05\n#D Two blocks\n#R 23/36\n#P -1 -1\n.*\n..*\n***\n#P 5 5\n*\n")
+            .unwrap();
+    assert_eq!(pattern.rule.as_deref(), Some("B36/S23"));
+    assert_eq!(pattern.comments, vec!["Two blocks"]);
+    assert_eq!(
+        (pattern.universe.width(), pattern.universe.height()),
+        (7, 7)
+    );
+
+    let written = life105::write(&Pattern::from(glider()));
+    assert_eq!(written, "#Life 1.05\n#N\n#P -1 -1\n.*\n..*\n***\n");
+    assert_eq!(life105::parse(&written).unwrap().universe, glider());
+    assert_eq!(
+        life105::parse("#Life 1.05\n**\n").unwrap_err().message,
+        "cell rows need a `#P` line first"
+    );
+    assert_eq!(
+        life105::parse("#Life 1.05\n#P 9223372036854775807 0\n**\n").unwrap_err(),
+        ParseError::new(3, 2, "cell position is out of range")
+    );
+}
+
+#[test]
+fn formats_are_detected_from_content() {
+    let pattern = Pattern::from(glider());
+    for format in formats::FORMATS.iter() {
+        let text = format.write(&pattern);
+        assert_eq!(formats::detect(&text).unwrap().name(), format.name());
+        assert_eq!(formats::parse_any(&text).unwrap().universe, glider());
+    }
+
+    assert_eq!(formats::by_extension(".cells").unwrap().name(), "Plaintext");
+    assert_eq!(formats::by_extension("RLE").unwrap().name(), "RLE");
+    assert!(formats::parse_any("<svg></svg>").is_err());
+}
+
+#[test]
+fn macrocell_loads_repeated_nodes_without_expanding() {
+    // A glider leaf tiled across a 2^20 square: over 5 billion live cells
+    let mut text = String::from("[M2] (golly 4.0)\n#R B3/S23\n.*$..*$***$\n");
+    for level in 4..=20 {
+        let child = level - 3;
+        text.push_str(&format!(
+            "{} {} {} {} {}\n",
+            level, child, child, child, child
+        ));
+    }
+
+    let pattern = macrocell::parse(&text).unwrap();
+    assert_eq!(pattern.rule.as_deref(), Some("B3/S23"));
+    assert_eq!(pattern.tree.level(), 20);
+    assert_eq!(pattern.tree.node_count(), 18);
+    assert_eq!(pattern.tree.population(), 5 << 34);
+    assert_eq!(
+        pattern.tree.bounding_box(),
+        Some((0, 0, (1 << 20) - 5, (1 << 20) - 5))
+    );
+
+    let window = pattern.tree.window((1 << 19) + 8, 8, 3, 3);
+    assert_eq!(window, glider());
+    assert!(formats::parse_any(&text).is_err());
+}
+
+#[test]
+fn macrocell_round_trips_through_writer() {
+    let mut board = Universe::empty(20, 12);
+    board.stamp(&glider(), 0, 0, StampMode::Or);
+    board.stamp(&glider(), 9, 17, StampMode::Or);
+
+    let tree = QuadTree::from_universe(&board);
+    assert_eq!(tree.level(), 5);
+    assert_eq!(tree.population(), 10);
+
+    let text = macrocell::write(&MacrocellPattern {
+        tree,
+        rule: None,
+        comments: vec![String::from("Two gliders")],
+    });
+    assert!(text.starts_with("[M2]"));
+    let pattern = macrocell::parse(&text).unwrap();
+    assert_eq!(pattern.comments, vec!["Two gliders"]);
+    assert_eq!(pattern.tree.window(0, 0, 12, 20), board);
+
+    assert_eq!(formats::detect(&text).unwrap().name(), "Macrocell");
+    assert_eq!(
+        macrocell::parse("[M2]\n.*$\n4 1 2 0 0\n").unwrap_err(),
+        ParseError::new(3, 5, "node 2 is not defined yet")
+    );
+}
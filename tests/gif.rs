@@ -0,0 +1,133 @@
+//! Native tests for animated GIF export.
+
+extern crate wasm_game_of_life;
+
+use wasm_game_of_life::export::gif::{self, GifOptions};
+use wasm_game_of_life::export::{RenderOptions, Rgba};
+use wasm_game_of_life::image::ImageError;
+use wasm_game_of_life::universe::{Region, Universe};
+
+struct Frame {
+    delay: u16,
+    width: u16,
+    height: u16,
+    indices: Vec<u8>,
+}
+
+fn decode(bytes: &[u8]) -> (Vec<u8>, Vec<Frame>) {
+    let mut options = ::gif::DecodeOptions::new();
+    options.set_color_output(::gif::ColorOutput::Indexed);
+    let mut decoder = options.read_info(bytes).unwrap();
+    let palette = decoder.global_palette().unwrap().to_vec();
+    let mut frames = vec![];
+    while let Some(frame) = decoder.read_next_frame().unwrap() {
+        frames.push(Frame {
+            delay: frame.delay,
+            width: frame.width,
+            height: frame.height,
+            indices: frame.buffer.to_vec(),
+        });
+    }
+    (palette, frames)
+}
+
+fn blinker() -> Universe {
+    let mut universe = Universe::empty(5, 5);
+    universe.set_cells(&[(2, 1), (2, 2), (2, 3)]);
+    universe
+}
+
+#[test]
+fn gif_records_each_generation() {
+    let options = GifOptions {
+        frames: 3,
+        delay: 25,
+        crop: None,
+        render: RenderOptions {
+            cell_size: 1,
+            grid: false,
+            alive_color: Rgba::opaque(255, 0, 0),
+            ..RenderOptions::default()
+        },
+    };
+    let universe = blinker();
+    let bytes = universe.to_gif(&options).unwrap();
+    assert!(bytes.starts_with(gif::SIGNATURE));
+
+    let (palette, frames) = decode(&bytes);
+    assert_eq!(&palette[6..9], &[255, 0, 0]);
+    assert_eq!(frames.len(), 3);
+
+    let live = |frame: &Frame| -> Vec<usize> {
+        (0..frame.indices.len())
+            .filter(|&idx| frame.indices[idx] == 2)
+            .collect()
+    };
+    assert_eq!(live(&frames[0]), vec![11, 12, 13]);
+    assert_eq!(live(&frames[1]), vec![7, 12, 17]);
+    assert_eq!(live(&frames[2]), vec![11, 12, 13]);
+    assert!(frames.iter().all(|frame| frame.delay == 25));
+
+    // The universe itself doesn't advance
+    assert_eq!(universe, blinker());
+}
+
+#[test]
+fn gif_crops_to_a_region() {
+    let options = GifOptions {
+        frames: 2,
+        crop: Some(Region {
+            row: 1,
+            column: 2,
+            height: 3,
+            width: 2,
+        }),
+        ..GifOptions::default()
+    };
+    let (_, frames) = decode(&blinker().to_gif(&options).unwrap());
+
+    // Two cells across, three down, with the grid
+    assert_eq!((frames[0].width, frames[0].height), (23, 34));
+}
+
+#[test]
+fn gif_compresses_large_frames() {
+    // Enough pixels and variety to fill and reset the LZW table
+    let mut universe = Universe::new();
+    universe.tick();
+    let options = GifOptions {
+        frames: 2,
+        ..GifOptions::default()
+    };
+    let bytes = universe.to_gif(&options).unwrap();
+    let (_, frames) = decode(&bytes);
+
+    let expected = wasm_game_of_life::export::render(&universe, &options.render).unwrap();
+    let render = &options.render;
+    let palette = [render.grid_color, render.dead_color, render.alive_color];
+    let indices: Vec<u8> = (0..expected.height)
+        .flat_map(|y| (0..expected.width).map(move |x| (x, y)))
+        .map(|(x, y)| {
+            let color = expected.get(x, y);
+            palette.iter().position(|&entry| entry == color).unwrap() as u8
+        })
+        .collect();
+    assert_eq!(frames[0].indices, indices);
+    assert!(bytes.len() < expected.pixels.len() / 4);
+}
+
+#[test]
+fn gif_rejects_frames_past_the_size_limit() {
+    let options = GifOptions {
+        frames: 1,
+        ..GifOptions::default()
+    };
+    let result = Universe::empty(7000, 1).to_gif(&options);
+    assert_eq!(
+        result,
+        Err(ImageError::TooLarge {
+            width: 77001,
+            height: 12
+        })
+    );
+}
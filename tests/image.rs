@@ -0,0 +1,125 @@
+//! Native tests for importing boards from images.
+
+extern crate wasm_game_of_life;
+
+mod common;
+
+use common::glider;
+use wasm_game_of_life::image::{self, Image, ImageError, ImportOptions};
+use wasm_game_of_life::universe::Universe;
+
+#[test]
+fn plain_pbm_reads_with_comments_and_packed_bits() {
+    let spaced = b"P1\n# A glider\n3 3\n0 1 0\n0 0 1\n1 1 1\n";
+    let packed = b"P1 3 3 010001111";
+    for bytes in [&spaced[..], &packed[..]].iter() {
+        let universe = Universe::from_image(bytes, &ImportOptions::default()).unwrap();
+        assert_eq!(universe, glider());
+    }
+}
+
+#[test]
+fn raw_pbm_rows_are_padded_to_bytes() {
+    let mut bytes = b"P4\n3 3\n".to_vec();
+    bytes.extend_from_slice(&[0b0100_0000, 0b0010_0000, 0b1110_0000]);
+    let universe = Universe::from_image(&bytes, &ImportOptions::default()).unwrap();
+    assert_eq!(universe, glider());
+}
+
+#[test]
+fn pgm_scales_gray_values_and_applies_the_threshold() {
+    let plain = image::decode(b"P2 3 1 4 0 2 4").unwrap();
+    assert_eq!(plain.pixels, vec![0, 127, 255]);
+
+    let mut raw = b"P5 2 1 65535\n".to_vec();
+    raw.extend_from_slice(&[0x00, 0x00, 0xFF, 0xFF]);
+    assert_eq!(image::decode(&raw).unwrap().pixels, vec![0, 255]);
+
+    let dark = plain.to_universe(128, false);
+    assert_eq!(dark.to_string(), "◼◼◻\n");
+    let light = plain.to_universe(128, true);
+    assert_eq!(light.to_string(), "◻◻◼\n");
+    let strict = plain.to_universe(100, false);
+    assert_eq!(strict.to_string(), "◼◻◻\n");
+}
+
+#[test]
+fn downscale_keeps_the_aspect_ratio() {
+    // Left half black, right half white
+    let pixels = (0..40 * 20)
+        .map(|idx| if idx % 40 < 20 { 0 } else { 255 })
+        .collect();
+    let image = Image {
+        width: 40,
+        height: 20,
+        pixels,
+    };
+
+    let small = image.downscale(10, 10);
+    assert_eq!((small.width, small.height), (10, 5));
+    assert_eq!(
+        &small.pixels[..10],
+        &[0, 0, 0, 0, 0, 255, 255, 255, 255, 255]
+    );
+
+    let options = ImportOptions {
+        fit: Some((64, 64)),
+        ..ImportOptions::default()
+    };
+    let universe = Universe::from_image(b"P1 3 3 010001111", &options).unwrap();
+    assert_eq!(universe, glider());
+}
+
+#[test]
+fn malformed_images_are_rejected() {
+    let truncated = image::decode(b"P1 3 3 0100");
+    assert!(matches!(truncated, Err(ImageError::Malformed(_))));
+
+    let raw_truncated = image::decode(b"P5 2 2 255\n\x00\x00");
+    assert!(matches!(raw_truncated, Err(ImageError::Malformed(_))));
+
+    let above_max = image::decode(b"P2 1 1 4 5");
+    assert!(matches!(above_max, Err(ImageError::Malformed(_))));
+
+    let huge = image::decode(b"P1 100000 100000 0");
+    assert_eq!(
+        huge,
+        Err(ImageError::TooLarge {
+            width: 100000,
+            height: 100000
+        })
+    );
+
+    let unknown = image::decode(b"GIF89a");
+    assert!(matches!(unknown, Err(ImageError::Unsupported(_))));
+}
+
+#[cfg(not(feature = "png"))]
+#[test]
+fn png_without_the_feature_is_reported() {
+    assert_eq!(
+        image::decode(b"\x89PNG\r\n\x1a\n"),
+        Err(ImageError::Unsupported(String::from(
+            "PNG support not compiled in"
+        )))
+    );
+}
+
+#[cfg(feature = "png")]
+#[test]
+fn png_reads_color_and_transparency() {
+    let mut bytes = vec![];
+    {
+        let mut encoder = png::Encoder::new(&mut bytes, 3, 1);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().unwrap();
+        // Opaque black, transparent black and opaque red
+        writer
+            .write_image_data(&[0, 0, 0, 255, 0, 0, 0, 0, 255, 0, 0, 255])
+            .unwrap();
+    }
+
+    let image = image::decode(&bytes).unwrap();
+    assert_eq!(image.pixels, vec![0, 255, 76]);
+}
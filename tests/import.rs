@@ -0,0 +1,96 @@
+//! Native tests for reading files dropped onto the page.
+
+extern crate wasm_game_of_life;
+
+mod common;
+
+use common::glider;
+use wasm_game_of_life::formats::{Macrocell, Pattern, PatternFormat};
+use wasm_game_of_life::image::ImportOptions;
+use wasm_game_of_life::import::{read_file, ImportError};
+use wasm_game_of_life::universe::Universe;
+
+fn read(name: &str, bytes: &[u8]) -> Result<Pattern, ImportError> {
+    read_file(name, bytes, &ImportOptions::default())
+}
+
+#[test]
+fn rle_is_recognized_by_its_contents() {
+    let rle = b"#N Glider\nx = 3, y = 3, rule = B3/S23\nbo$2bo$3o!\n";
+    for name in ["glider.rle", "glider.txt", "glider"].iter() {
+        let pattern = read(name, rle).unwrap();
+        assert_eq!(pattern.universe, glider());
+        assert_eq!(pattern.name.as_deref(), Some("Glider"));
+    }
+}
+
+#[test]
+fn plaintext_reads_by_contents_or_extension() {
+    let cells = b"!Name: Glider\n.O.\n..O\nOOO\n";
+    assert_eq!(read("glider.cells", cells).unwrap().universe, glider());
+    assert_eq!(read("glider", cells).unwrap().universe, glider());
+}
+
+#[test]
+fn macrocell_reads_by_contents() {
+    let text = Macrocell.write(&Pattern::from(glider()));
+    let pattern = read("glider.mc", text.as_bytes()).unwrap();
+    assert_eq!(pattern.universe, glider());
+}
+
+#[test]
+fn huge_macrocells_are_cut_to_fit_without_expanding() {
+    // One cell in each of the top left and bottom right corners of a
+    // 2^40-wide tree, far past what a dense universe can hold
+    let mut text = String::from("[M2]\n*$\n");
+    for level in 4..=40 {
+        let child = level - 3;
+        text.push_str(&format!("{} {} 0 0 {}\n", level, child, child));
+    }
+
+    let options = ImportOptions {
+        fit: Some((10, 8)),
+        ..ImportOptions::default()
+    };
+    let pattern = read_file("huge.mc", text.as_bytes(), &options).unwrap();
+    let mut expected = Universe::empty(10, 8);
+    expected.set_cells(&[(0, 0)]);
+    assert_eq!(pattern.universe, expected);
+
+    assert!(matches!(
+        read("huge.mc", text.as_bytes()),
+        Err(ImportError::Pattern(_))
+    ));
+}
+
+#[test]
+fn images_read_by_extension_or_magic() {
+    let pbm = b"P1 3 3 010001111";
+    assert_eq!(read("glider.pbm", pbm).unwrap().universe, glider());
+    assert_eq!(read("glider.dat", pbm).unwrap().universe, glider());
+}
+
+#[test]
+fn malformed_images_report_image_errors() {
+    assert!(matches!(
+        read("glider.png", b"not a png"),
+        Err(ImportError::Image(_))
+    ));
+}
+
+#[test]
+fn unknown_files_are_unrecognized() {
+    assert_eq!(read("notes.doc", b"hello"), Err(ImportError::Unrecognized));
+    assert_eq!(
+        read("glider.bin", &[0xFF, 0xFE, 0x00]),
+        Err(ImportError::Unrecognized)
+    );
+}
+
+#[test]
+fn parse_errors_are_passed_through() {
+    assert!(matches!(
+        read("broken.rle", b"x = 3, y = 3\nbo$2b?$3o!"),
+        Err(ImportError::Pattern(_))
+    ));
+}
@@ -0,0 +1,61 @@
+//! Native tests for the built-in pattern library.
+
+extern crate wasm_game_of_life;
+
+use wasm_game_of_life::apgcode;
+use wasm_game_of_life::formats::rle;
+use wasm_game_of_life::library::{self, CATEGORIES, PATTERNS};
+
+#[test]
+fn every_pattern_parses_and_matches_its_metadata() {
+    for pattern in PATTERNS.iter() {
+        let parsed = rle::parse(pattern.source).unwrap();
+        assert_eq!(
+            parsed.name.as_deref(),
+            Some(pattern.name),
+            "{}",
+            pattern.key
+        );
+        assert_eq!(
+            parsed.rule.as_deref(),
+            Some(pattern.rule),
+            "{}",
+            pattern.key
+        );
+        assert!(CATEGORIES.contains(&pattern.category));
+        assert_eq!(library::get(pattern.key).unwrap().key, pattern.key);
+    }
+    assert!(library::get("missing").is_none());
+}
+
+#[test]
+fn periods_agree_with_apgcodes() {
+    for pattern in PATTERNS.iter() {
+        let code = apgcode::encode(&pattern.universe());
+        match (pattern.period, code.as_deref()) {
+            (Some(1), Some(code)) => assert!(code.starts_with("xs"), "{}", pattern.key),
+            (Some(period), Some(code)) => {
+                assert_eq!(code[2..code.find('_').unwrap()], period.to_string())
+            }
+            (None, Some(code)) => panic!("{} is periodic: {}", pattern.key, code),
+            // Guns grow forever and methuselahs take too long to settle
+            (_, None) => assert_ne!(pattern.period, Some(1), "{}", pattern.key),
+        }
+    }
+}
+
+#[test]
+fn library_matches_the_old_html_prefabs() {
+    let gun = "000000000000000000000000100000000000000000000000000000000010100000000000000000000000110000001100000000000011000000000001000100001100000000000011110000000010000010001100000000000000110000000010001011000010100000000000000000000010000010000000100000000000000000000001000100000000000000000000000000000000110000000000000000000000";
+    let pulsar = "0011100011100000000000000010000101000011000010100001100001010000100111000111000000000000000001110001110010000101000011000010100001100001010000100000000000000011100011100";
+
+    for (key, expected) in [("gosper-glider-gun", gun), ("pulsar", pulsar)].iter() {
+        let universe = library::get(key).unwrap().universe();
+        let actual: String = universe
+            .cells()
+            .iter()
+            .map(|&cell| if cell as u8 == 1 { '1' } else { '0' })
+            .collect();
+        assert_eq!(&actual, expected, "{}", key);
+    }
+}
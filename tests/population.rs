@@ -0,0 +1,110 @@
+//! Native tests for the population history behind the graph.
+
+extern crate wasm_game_of_life;
+
+use wasm_game_of_life::population::{Marker, MarkerKind, PopulationHistory, Sample};
+use wasm_game_of_life::universe::Universe;
+
+fn blinker() -> Universe {
+    let mut universe = Universe::empty(5, 5);
+    universe.set_cells(&[(2, 1), (2, 2), (2, 3)]);
+    universe
+}
+
+#[test]
+fn samples_are_kept_in_a_ring_buffer() {
+    let mut universe = blinker();
+    let mut history = PopulationHistory::new(3);
+    history.record(&universe);
+    for _ in 0..4 {
+        universe.tick();
+        history.record(&universe);
+    }
+    let generations: Vec<u64> = history.samples().iter().map(|s| s.generation).collect();
+    assert_eq!(generations, vec![2, 3, 4]);
+}
+
+#[test]
+fn edits_replace_the_sample_and_add_markers() {
+    let mut universe = blinker();
+    let mut history = PopulationHistory::default();
+    history.record(&universe);
+    universe.toggle_cell(0, 0);
+    history.mark(&universe, MarkerKind::Edit);
+    history.mark(&universe, MarkerKind::Edit);
+    history.mark(&universe, MarkerKind::Pause);
+
+    assert_eq!(
+        history.samples().iter().cloned().collect::<Vec<_>>(),
+        vec![Sample {
+            generation: 0,
+            population: 4
+        }]
+    );
+    assert_eq!(
+        history.markers().iter().cloned().collect::<Vec<_>>(),
+        vec![
+            Marker {
+                generation: 0,
+                kind: MarkerKind::Edit
+            },
+            Marker {
+                generation: 0,
+                kind: MarkerKind::Pause
+            },
+        ]
+    );
+}
+
+#[test]
+fn old_markers_fall_off_with_their_samples() {
+    let mut universe = blinker();
+    let mut history = PopulationHistory::new(2);
+    history.mark(&universe, MarkerKind::Pause);
+    universe.tick();
+    history.record(&universe);
+    assert_eq!(history.markers().len(), 1);
+    universe.tick();
+    history.record(&universe);
+    assert!(history.markers().is_empty());
+}
+
+#[test]
+fn going_back_a_generation_starts_over() {
+    let mut universe = blinker();
+    let mut history = PopulationHistory::default();
+    universe.set_generation(10);
+    history.mark(&universe, MarkerKind::Edit);
+    universe.set_generation(3);
+    history.record(&universe);
+    assert_eq!(history.samples().len(), 1);
+    assert!(history.markers().is_empty());
+}
+
+#[test]
+fn scale_rounds_up_to_a_round_number() {
+    let mut history = PopulationHistory::default();
+    assert_eq!(history.scale(), 1);
+    for (population, scale) in [(3, 5), (7, 10), (11, 20), (200, 200), (201, 500)].iter() {
+        let mut universe = Universe::empty(*population, 1);
+        universe.set_cells(&(0..*population).map(|col| (0, col)).collect::<Vec<_>>());
+        history.clear();
+        history.record(&universe);
+        assert_eq!(history.scale(), *scale);
+    }
+}
+
+#[test]
+fn csv_lists_samples_with_their_events() {
+    let mut universe = blinker();
+    let mut history = PopulationHistory::default();
+    history.record(&universe);
+    universe.tick();
+    history.mark(&universe, MarkerKind::Pause);
+    universe.clear();
+    history.mark(&universe, MarkerKind::Edit);
+    assert_eq!(
+        history.to_csv(),
+        "generation,population,events\n0,3,\n1,0,pause;edit\n"
+    );
+}
@@ -0,0 +1,140 @@
+//! Native tests for the predecessor search.
+
+extern crate wasm_game_of_life;
+
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::time::Duration;
+
+use wasm_game_of_life::predecessor::{
+    find_predecessor, PredecessorError, PredecessorOptions, PredecessorSearch, Progress,
+};
+use wasm_game_of_life::universe::{Region, StampMode, Universe};
+
+fn pattern(width: u32, height: u32, cells: &[(u32, u32)]) -> Universe {
+    let mut universe = Universe::empty(width, height);
+    universe.set_cells(cells);
+    universe
+}
+
+// The target's window after a tick of `parent` on an otherwise empty plane
+fn child_window(parent: &Universe, target: &Universe) -> Universe {
+    let mut board = Universe::empty(parent.width() + 4, parent.height() + 4);
+    board.stamp(parent, 2, 2, StampMode::Or);
+    board.tick();
+    board.copy_region(Region {
+        row: 3,
+        column: 3,
+        height: target.height(),
+        width: target.width(),
+    })
+}
+
+fn torus_options(forward_checking: bool) -> PredecessorOptions {
+    PredecessorOptions {
+        torus: true,
+        forward_checking,
+        ..PredecessorOptions::default()
+    }
+}
+
+#[test]
+fn finds_parents_on_the_plane() {
+    let blinker = pattern(3, 1, &[(0, 0), (0, 1), (0, 2)]);
+    let glider = pattern(3, 3, &[(0, 1), (1, 2), (2, 0), (2, 1), (2, 2)]);
+    let block = pattern(4, 4, &[(1, 1), (1, 2), (2, 1), (2, 2)]);
+    for target in [blinker, glider, block].iter() {
+        for &forward_checking in [true, false].iter() {
+            let options = PredecessorOptions {
+                forward_checking,
+                ..PredecessorOptions::default()
+            };
+            let parent = find_predecessor(target, options).unwrap();
+            assert_eq!(
+                (parent.width(), parent.height()),
+                (target.width() + 2, target.height() + 2)
+            );
+            assert_eq!(child_window(&parent, target), *target);
+        }
+    }
+}
+
+#[test]
+fn agrees_with_brute_force_on_small_tori() {
+    // Which 3x3 torus boards some 3x3 torus board ticks into
+    let mut reachable = vec![false; 512];
+    for bits in 0..512u32 {
+        let cells: Vec<(u32, u32)> = (0..9)
+            .filter(|bit| bits & (1 << bit) != 0)
+            .map(|bit| (bit / 3, bit % 3))
+            .collect();
+        let mut board = pattern(3, 3, &cells);
+        board.tick();
+        let child = (0..9).fold(0, |acc, idx| acc | ((board.cells()[idx] as usize) << idx));
+        reachable[child] = true;
+    }
+    assert!(reachable.iter().any(|&found| !found));
+
+    for bits in 0..512u32 {
+        let cells: Vec<(u32, u32)> = (0..9)
+            .filter(|bit| bits & (1 << bit) != 0)
+            .map(|bit| (bit / 3, bit % 3))
+            .collect();
+        let target = pattern(3, 3, &cells);
+        for &forward_checking in [true, false].iter() {
+            match find_predecessor(&target, torus_options(forward_checking)) {
+                Ok(mut parent) => {
+                    assert!(reachable[bits as usize]);
+                    parent.tick();
+                    assert_eq!(parent.cells(), target.cells());
+                }
+                Err(error) => {
+                    assert_eq!(error, PredecessorError::GardenOfEden);
+                    assert!(!reachable[bits as usize], "{:b} has a parent", bits);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn searches_resume_across_steps() {
+    let target = pattern(5, 5, &[(1, 1), (1, 2), (1, 3), (3, 2)]);
+    let mut search = PredecessorSearch::new(&target, PredecessorOptions::default());
+    let mut steps = 0;
+    let parent = loop {
+        steps += 1;
+        if let Progress::Done(result) = search.run(5) {
+            break result.unwrap();
+        }
+    };
+    assert!(steps > 1);
+    assert!(search.nodes() > 0);
+    assert_eq!(child_window(&parent, &target), target);
+    assert_eq!(search.run(5), Progress::Done(Ok(parent)));
+}
+
+#[test]
+fn stops_when_cancelled_or_out_of_time() {
+    let target = pattern(12, 12, &[(5, 5)]);
+    let cancel = Arc::new(AtomicBool::new(true));
+    let options = PredecessorOptions {
+        cancel: Some(Arc::clone(&cancel)),
+        ..PredecessorOptions::default()
+    };
+    assert_eq!(
+        find_predecessor(&target, options),
+        Err(PredecessorError::Cancelled)
+    );
+
+    let options = PredecessorOptions {
+        time_limit: Some(Duration::from_millis(1)),
+        ..PredecessorOptions::default()
+    };
+    let mut search = PredecessorSearch::new(&target, options);
+    std::thread::sleep(Duration::from_millis(5));
+    assert_eq!(
+        search.run(100),
+        Progress::Done(Err(PredecessorError::TimedOut))
+    );
+}
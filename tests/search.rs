@@ -0,0 +1,112 @@
+//! Native tests for finding patterns on the board.
+
+extern crate wasm_game_of_life;
+
+mod common;
+
+use common::glider;
+use wasm_game_of_life::search::{Orientation, SearchOptions};
+use wasm_game_of_life::universe::{Region, StampMode, Universe};
+
+fn found(board: &Universe, pattern: &Universe, padding: u32) -> Vec<(u32, u32, Orientation)> {
+    board
+        .search(pattern, &SearchOptions { padding })
+        .into_iter()
+        .map(|found| (found.region.row, found.region.column, found.orientation))
+        .collect()
+}
+
+#[test]
+fn finds_every_orientation() {
+    let mut board = Universe::empty(30, 30);
+    board.stamp(&glider(), 2, 2, StampMode::Or);
+    board.stamp(&glider().rotate_90(), 2, 20, StampMode::Or);
+    board.stamp(&glider().flip_vertical(), 20, 2, StampMode::Or);
+    board.stamp(&glider().transpose().rotate_180(), 20, 20, StampMode::Or);
+
+    let matches = found(&board, &glider(), 0);
+    assert_eq!(matches.len(), 4);
+    assert_eq!(matches[0], (2, 2, Orientation::Identity));
+    // The glider's own symmetry means some orientations look alike, so only
+    // the positions are certain for the rest
+    let positions: Vec<(u32, u32)> = matches.iter().map(|&(row, col, _)| (row, col)).collect();
+    assert_eq!(positions, vec![(2, 2), (2, 20), (20, 2), (20, 20)]);
+    for &(row, col, orientation) in matches.iter() {
+        let region = Region {
+            row,
+            column: col,
+            height: 3,
+            width: 3,
+        };
+        assert_eq!(board.copy_region(region), orientation.apply(&glider()));
+    }
+}
+
+#[test]
+fn symmetric_patterns_match_once_per_position() {
+    let mut block = Universe::empty(2, 2);
+    block.set_cells(&[(0, 0), (0, 1), (1, 0), (1, 1)]);
+    let mut board = Universe::empty(10, 10);
+    board.stamp(&block, 4, 4, StampMode::Or);
+    assert_eq!(
+        found(&board, &block, 0),
+        vec![(4, 4, Orientation::Identity)]
+    );
+}
+
+#[test]
+fn dead_cells_in_the_bounding_box_must_match() {
+    let mut board = Universe::empty(10, 10);
+    board.stamp(&glider(), 3, 3, StampMode::Or);
+    board.toggle_cell(3, 3);
+    assert!(found(&board, &glider(), 0).is_empty());
+}
+
+#[test]
+fn padding_requires_isolation() {
+    let mut board = Universe::empty(20, 20);
+    board.stamp(&glider(), 2, 2, StampMode::Or);
+    board.stamp(&glider(), 12, 12, StampMode::Or);
+    // A stray cell two away from the second glider
+    board.set_cells(&[(12, 16)]);
+
+    assert_eq!(found(&board, &glider(), 0).len(), 2);
+    assert_eq!(
+        found(&board, &glider(), 2),
+        vec![(2, 2, Orientation::Identity)]
+    );
+    assert_eq!(found(&board, &glider(), 1).len(), 2);
+}
+
+#[test]
+fn matches_wrap_around_the_edges() {
+    let mut board = Universe::empty(10, 8);
+    board.stamp(&glider(), -1, 8, StampMode::Or);
+    let matches = board.search(&glider(), &SearchOptions::default());
+    assert_eq!(matches.len(), 1);
+    assert_eq!(
+        matches[0].region,
+        Region {
+            row: 7,
+            column: 8,
+            height: 3,
+            width: 3
+        }
+    );
+}
+
+#[test]
+fn patterns_are_cropped_and_empty_ones_match_nothing() {
+    let mut padded = Universe::empty(7, 7);
+    padded.stamp(&glider(), 2, 2, StampMode::Or);
+    let mut board = Universe::empty(10, 10);
+    board.stamp(&glider(), 0, 0, StampMode::Or);
+    assert_eq!(
+        found(&board, &padded, 0),
+        vec![(0, 0, Orientation::Identity)]
+    );
+    assert!(found(&board, &Universe::empty(3, 3), 0).is_empty());
+
+    // Too big to fit on the board with its padding
+    assert!(found(&board, &glider(), 4).is_empty());
+}
@@ -0,0 +1,83 @@
+//! Native tests for the optional `serde` support. Run with
+//! `cargo test --features serde`.
+
+#![cfg(feature = "serde")]
+
+extern crate wasm_game_of_life;
+
+mod common;
+
+use common::glider;
+use wasm_game_of_life::cell::Cell;
+use wasm_game_of_life::formats::{rle, Pattern};
+use wasm_game_of_life::snapshot::Snapshot;
+use wasm_game_of_life::universe::{StampMode, Universe};
+
+#[test]
+fn cells_serialize_as_numbers() {
+    assert_eq!(serde_json::to_string(&Cell::Alive).unwrap(), "1");
+    assert_eq!(serde_json::from_str::<Cell>("0").unwrap(), Cell::Dead);
+    assert!(serde_json::from_str::<Cell>("2").is_err());
+}
+
+#[test]
+fn universe_serializes_compactly() {
+    let json = serde_json::to_string(&glider()).unwrap();
+    assert_eq!(
+        json,
+        r#"{"width":3,"height":3,"generation":0,"cells":"bo3b4o"}"#
+    );
+
+    let mut universe = Universe::new();
+    universe.tick();
+    let json = serde_json::to_string(&universe).unwrap();
+    assert!(json.len() < (universe.width() * universe.height()) as usize);
+    assert_eq!(serde_json::from_str::<Universe>(&json).unwrap(), universe);
+}
+
+#[test]
+fn universe_rejects_cells_that_dont_match_its_size() {
+    let short = r#"{"width":3,"height":3,"cells":"bo3b3o"}"#;
+    let long = r#"{"width":3,"height":3,"cells":"bo3b5o"}"#;
+    let bad = r#"{"width":3,"height":3,"cells":"bo3b4x"}"#;
+    for json in [short, long, bad].iter() {
+        assert!(serde_json::from_str::<Universe>(json).is_err(), "{}", json);
+    }
+
+    // The generation is optional and defaults to zero
+    let universe: Universe =
+        serde_json::from_str(r#"{"width":3,"height":3,"cells":"bo3b4o"}"#).unwrap();
+    assert_eq!(universe, glider());
+}
+
+#[test]
+fn pattern_and_snapshot_keep_their_metadata() {
+    let pattern =
+        rle::parse("#N Glider\n#C A spaceship.\nx = 3, y = 3, rule = B3/S23\nbo$2bo$3o!").unwrap();
+    let json = serde_json::to_string(&pattern).unwrap();
+    assert_eq!(serde_json::from_str::<Pattern>(&json).unwrap(), pattern);
+
+    let mut snapshot = Snapshot::new(glider());
+    snapshot.rule = String::from("B36/S23");
+    let json = serde_json::to_string(&snapshot).unwrap();
+    assert!(json.contains("B36/S23"));
+    assert_eq!(serde_json::from_str::<Snapshot>(&json).unwrap(), snapshot);
+}
+
+#[test]
+fn census_serializes_as_a_report() {
+    let mut universe = Universe::empty(8, 8);
+    universe.stamp(&glider(), 1, 1, StampMode::Or);
+    let json = serde_json::to_value(universe.census()).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "objects": [{
+                "region": {"row": 1, "column": 1, "height": 3, "width": 3},
+                "population": 5,
+                "apgcode": "xq4_153",
+                "name": "glider",
+            }]
+        })
+    );
+}
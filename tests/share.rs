@@ -0,0 +1,114 @@
+//! Native tests for boards shared through the location hash.
+
+extern crate wasm_game_of_life;
+
+mod common;
+
+use common::glider_on;
+use wasm_game_of_life::formats::{rle, Pattern};
+use wasm_game_of_life::share::{ShareError, SharedBoard, MAX_HASH_LENGTH};
+use wasm_game_of_life::universe::Universe;
+
+#[test]
+fn hash_round_trips_board_rule_and_speed() {
+    let mut pattern = Pattern::from(glider_on(20, 10));
+    pattern.rule = Some(String::from("B36/S23"));
+    let shared = SharedBoard { pattern, speed: 4 };
+
+    let hash = shared.to_hash();
+    assert!(hash
+        .chars()
+        .all(|ch| ch.is_ascii_alphanumeric() || "-_=&".contains(ch)));
+
+    let restored = SharedBoard::from_hash(&format!("#{}", hash)).unwrap();
+    assert_eq!(restored.pattern.universe, glider_on(20, 10));
+    assert_eq!(restored.pattern.rule.as_deref(), Some("B36/S23"));
+    assert_eq!(restored.speed, 4);
+}
+
+#[test]
+fn hash_reads_the_documented_example() {
+    let shared =
+        SharedBoard::from_hash("#rle=eCA9IDMsIHkgPSAzLCBydWxlID0gQjMvUzIzCmJvJDJibyQzbyEK&speed=2")
+            .unwrap();
+    assert_eq!(
+        shared.pattern.universe.to_rle(),
+        "x = 3, y = 3, rule = B3/S23\nbo$2bo$3o!\n"
+    );
+    assert_eq!(shared.pattern.rule.as_deref(), Some(rle::DEFAULT_RULE));
+    assert_eq!(shared.speed, 2);
+
+    // Speed is optional and unknown keys are skipped
+    let shared = SharedBoard::from_hash(
+        "theme=dark&rle=eCA9IDMsIHkgPSAzLCBydWxlID0gQjMvUzIzCmJvJDJibyQzbyEK",
+    )
+    .unwrap();
+    assert_eq!(shared.speed, 1);
+}
+
+#[test]
+fn bad_hashes_are_rejected() {
+    let malformed =
+        |hash: &str| matches!(SharedBoard::from_hash(hash), Err(ShareError::Malformed(_)));
+    assert!(malformed(""));
+    assert!(malformed("speed=2"));
+    assert!(malformed("rle=not*base64"));
+    assert!(malformed(
+        "rle=eCA9IDMsIHkgPSAzLCBydWxlID0gQjMvUzIzCmJvJDJibyQzbyEK&speed=0"
+    ));
+    assert!(malformed(
+        "rle=eCA9IDMsIHkgPSAzLCBydWxlID0gQjMvUzIzCmJvJDJibyQzbyEK&speed=fast"
+    ));
+
+    // Valid base64, but `x = 2, y = 1` then `bq!` isn't RLE
+    assert!(matches!(
+        SharedBoard::from_hash("rle=eCA9IDIsIHkgPSAxCmJxIQo"),
+        Err(ShareError::Pattern(_))
+    ));
+
+    let long = "a".repeat(MAX_HASH_LENGTH + 1);
+    assert_eq!(
+        SharedBoard::from_hash(&long),
+        Err(ShareError::TooLong(MAX_HASH_LENGTH + 1))
+    );
+
+    let huge = SharedBoard {
+        pattern: Pattern::from(Universe::empty(2000, 1)),
+        speed: 1,
+    };
+    assert_eq!(
+        SharedBoard::from_hash(&huge.to_hash()),
+        Err(ShareError::TooLarge {
+            width: 2000,
+            height: 1
+        })
+    );
+}
+
+#[test]
+fn oversized_headers_are_rejected_before_decoding() {
+    // `x = 4000, y = 4000` then `3999o!`, small enough to decode but wider
+    // than a shared board may be
+    assert_eq!(
+        SharedBoard::from_hash("rle=eCA9IDQwMDAsIHkgPSA0MDAwCjM5OTlvIQo"),
+        Err(ShareError::TooLarge {
+            width: 4000,
+            height: 4000
+        })
+    );
+
+    // `x = 4294967295, y = 4294967295` then `o!`
+    assert_eq!(
+        SharedBoard::from_hash("rle=eCA9IDQyOTQ5NjcyOTUsIHkgPSA0Mjk0OTY3Mjk1Cm8hCg"),
+        Err(ShareError::TooLarge {
+            width: u32::MAX,
+            height: u32::MAX
+        })
+    );
+
+    // `67108864o!` with no header, within `MAX_CELLS` but not `MAX_SIDE`
+    assert!(matches!(
+        SharedBoard::from_hash("rle=NjcxMDg4NjRvIQo"),
+        Err(ShareError::Malformed(_))
+    ));
+}
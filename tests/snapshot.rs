@@ -0,0 +1,123 @@
+//! Native tests for the binary snapshot format.
+
+extern crate wasm_game_of_life;
+
+mod common;
+
+use common::glider_on;
+use wasm_game_of_life::checksum::crc32;
+use wasm_game_of_life::snapshot::{Snapshot, SnapshotError, Topology};
+use wasm_game_of_life::universe::Universe;
+
+#[test]
+fn crc32_matches_the_standard_check_value() {
+    assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    assert_eq!(crc32(b""), 0);
+}
+
+#[test]
+fn snapshot_round_trips_state() {
+    let mut universe = glider_on(20, 12);
+    for _ in 0..5 {
+        universe.tick();
+    }
+    let snapshot = Snapshot {
+        universe: universe.clone(),
+        rule: String::from("B36/S23"),
+        topology: Topology::Torus,
+    };
+
+    let decoded = Snapshot::decode(&snapshot.encode()).unwrap();
+
+    assert_eq!(decoded, snapshot);
+    assert_eq!(decoded.universe.generation(), 5);
+}
+
+#[test]
+fn snapshot_picks_the_smaller_cell_encoding() {
+    let sparse = glider_on(200, 120);
+    let mut dense = Universe::empty(16, 16);
+    let cells: Vec<(u32, u32)> = (0..16)
+        .flat_map(|row| (0..16).map(move |col| (row, col)))
+        .filter(|&(row, col)| (row + col) % 2 == 0)
+        .collect();
+    dense.set_cells(&cells);
+
+    // Five live cells take a few varints, far less than 24000 bits
+    let sparse_bytes = sparse.to_snapshot();
+    assert!(sparse_bytes.len() < 100);
+    assert_eq!(Universe::from_snapshot(&sparse_bytes).unwrap(), sparse);
+
+    // A checkerboard is one run per cell, so bits win
+    let dense_bytes = dense.to_snapshot();
+    assert!(dense_bytes.len() < 16 * 16 / 8 + 40);
+    assert_eq!(Universe::from_snapshot(&dense_bytes).unwrap(), dense);
+
+    let full = Universe::new();
+    assert_eq!(Universe::from_snapshot(&full.to_snapshot()).unwrap(), full);
+    let empty = Universe::empty(0, 0);
+    assert_eq!(
+        Universe::from_snapshot(&empty.to_snapshot()).unwrap(),
+        empty
+    );
+}
+
+#[test]
+fn snapshot_rejects_truncated_input() {
+    let bytes = glider_on(20, 12).to_snapshot();
+    for len in 0..bytes.len() {
+        assert_eq!(
+            Snapshot::decode(&bytes[..len]),
+            Err(SnapshotError::Truncated),
+            "length {}",
+            len
+        );
+    }
+}
+
+#[test]
+fn snapshot_rejects_corrupt_input() {
+    let bytes = glider_on(20, 12).to_snapshot();
+
+    let mut flipped = bytes.clone();
+    let last_cell_byte = bytes.len() - 5;
+    flipped[last_cell_byte] ^= 0x01;
+    assert!(matches!(
+        Snapshot::decode(&flipped),
+        Err(SnapshotError::ChecksumMismatch { .. })
+    ));
+
+    let mut magic = bytes.clone();
+    magic[0] = b'X';
+    assert_eq!(Snapshot::decode(&magic), Err(SnapshotError::BadMagic));
+
+    let mut version = bytes.clone();
+    version[4] = 99;
+    assert_eq!(
+        Snapshot::decode(&version),
+        Err(SnapshotError::UnsupportedVersion(99))
+    );
+
+    let mut trailing = bytes.clone();
+    trailing.push(0);
+    assert!(matches!(
+        Snapshot::decode(&trailing),
+        Err(SnapshotError::Corrupt(_))
+    ));
+}
+
+#[test]
+fn snapshot_rejects_cells_that_dont_fit_the_board() {
+    let bytes = glider_on(20, 12).to_snapshot();
+
+    // Shrink the board to 10 wide and fix up the checksum
+    let mut resized = bytes[..bytes.len() - 4].to_vec();
+    resized[6..10].copy_from_slice(&10u32.to_le_bytes());
+    let checksum = crc32(&resized);
+    resized.extend_from_slice(&checksum.to_le_bytes());
+
+    assert!(matches!(
+        Snapshot::decode(&resized),
+        Err(SnapshotError::Corrupt(_))
+    ));
+}
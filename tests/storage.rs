@@ -0,0 +1,85 @@
+//! Native tests for the save slot format. The `localStorage` calls
+//! themselves need a browser.
+
+extern crate wasm_game_of_life;
+
+mod common;
+
+use common::glider_on;
+use wasm_game_of_life::formats::Pattern;
+use wasm_game_of_life::share::SharedBoard;
+use wasm_game_of_life::snapshot::{Snapshot, SnapshotError};
+use wasm_game_of_life::storage::{self, SaveError, SavedBoard};
+use wasm_game_of_life::universe::Universe;
+
+fn saved_glider() -> SavedBoard {
+    let mut universe = glider_on(16, 8);
+    universe.tick();
+    SavedBoard {
+        snapshot: Snapshot::new(universe),
+        speed: 3,
+    }
+}
+
+#[test]
+fn saved_board_round_trips_through_a_string() {
+    let saved = saved_glider();
+    let value = saved.to_value();
+    assert!(value.starts_with("speed=3&snapshot="));
+
+    let restored = SavedBoard::from_value(&value).unwrap();
+    assert_eq!(restored, saved);
+    assert_eq!(restored.snapshot.universe.generation(), 1);
+}
+
+#[test]
+fn saved_board_rejects_damaged_values() {
+    let value = saved_glider().to_value();
+
+    assert!(matches!(
+        SavedBoard::from_value("speed=3"),
+        Err(SaveError::Malformed(_))
+    ));
+    assert!(matches!(
+        SavedBoard::from_value(&value.replace("speed=3", "speed=fast")),
+        Err(SaveError::Malformed(_))
+    ));
+    assert_eq!(
+        SavedBoard::from_value(&value[..value.len() - 8]),
+        Err(SaveError::Snapshot(SnapshotError::Truncated))
+    );
+}
+
+#[test]
+fn slots_are_namespaced() {
+    assert_eq!(storage::slot_key("Gun"), "wasm-game-of-life/slot/Gun");
+    assert!(!storage::slot_key("autosave").starts_with(storage::AUTOSAVE_KEY));
+}
+
+#[test]
+fn reload_after_an_edit_restores_the_autosave() {
+    // An edit writes the same hash to the address bar and the autosave
+    let saved = saved_glider();
+    let shared = SharedBoard {
+        pattern: Pattern::from(saved.snapshot.universe.clone()),
+        speed: saved.speed,
+    };
+    let hash = shared.to_hash();
+    assert!(storage::autosave_matches(
+        &format!("#{}", hash),
+        Some(&hash)
+    ));
+    assert!(storage::autosave_matches("", Some(&hash)));
+    assert!(storage::autosave_matches("#", None));
+
+    // A link from elsewhere wins over the last session
+    let link = SharedBoard {
+        pattern: Pattern::from(Universe::empty(4, 4)),
+        speed: 1,
+    };
+    assert!(!storage::autosave_matches(
+        &format!("#{}", link.to_hash()),
+        Some(&hash)
+    ));
+    assert!(!storage::autosave_matches(&format!("#{}", hash), None));
+}
@@ -0,0 +1,212 @@
+//! Native tests for the simulation model.
+
+extern crate wasm_game_of_life;
+
+mod common;
+
+use common::{glider, glider_on};
+use wasm_game_of_life::cell::Cell;
+use wasm_game_of_life::universe::{Charset, Region, StampMode, Universe};
+
+fn alive_cells(universe: &Universe) -> Vec<(u32, u32)> {
+    let mut alive = vec![];
+    for row in 0..universe.height() {
+        for col in 0..universe.width() {
+            if universe.cells()[universe.get_index(row, col)] == Cell::Alive {
+                alive.push((row, col));
+            }
+        }
+    }
+    alive
+}
+
+#[test]
+fn stamp_modes_combine_with_board() {
+    let mut pattern = Universe::empty(2, 1);
+    pattern.set_cells(&[(0, 0)]);
+
+    let mut board = Universe::empty(4, 4);
+    board.set_cells(&[(1, 1), (1, 2)]);
+
+    let mut or = board.clone();
+    or.stamp(&pattern, 1, 1, StampMode::Or);
+    assert_eq!(alive_cells(&or), vec![(1, 1), (1, 2)]);
+
+    let mut xor = board.clone();
+    xor.stamp(&pattern, 1, 1, StampMode::Xor);
+    assert_eq!(alive_cells(&xor), vec![(1, 2)]);
+
+    let mut and = board.clone();
+    and.stamp(&pattern, 1, 1, StampMode::And);
+    assert_eq!(alive_cells(&and), vec![(1, 1)]);
+
+    let mut replace = board.clone();
+    replace.stamp(&pattern, 1, 1, StampMode::Replace);
+    assert_eq!(alive_cells(&replace), vec![(1, 1)]);
+
+    let mut erase = board.clone();
+    erase.stamp(&pattern, 1, 1, StampMode::Erase);
+    assert_eq!(alive_cells(&erase), vec![(1, 2)]);
+}
+
+#[test]
+fn stamp_wraps_around_edges() {
+    let mut pattern = Universe::empty(2, 2);
+    pattern.set_cells(&[(0, 0), (0, 1), (1, 0), (1, 1)]);
+
+    let mut board = Universe::empty(4, 4);
+    board.stamp(&pattern, -1, 3, StampMode::Or);
+    assert_eq!(alive_cells(&board), vec![(0, 0), (0, 3), (3, 0), (3, 3)]);
+}
+
+#[test]
+fn stamp_ignores_empty_boards_and_patterns() {
+    let mut pattern = Universe::empty(2, 2);
+    pattern.set_cells(&[(0, 0), (1, 1)]);
+
+    let mut empty_board = Universe::empty(0, 0);
+    empty_board.stamp(&pattern, 1, -1, StampMode::Or);
+    assert_eq!(empty_board, Universe::empty(0, 0));
+
+    let mut board = Universe::empty(4, 4);
+    board.stamp(&Universe::empty(0, 0), 1, 1, StampMode::Replace);
+    assert_eq!(board, Universe::empty(4, 4));
+}
+
+#[test]
+fn transformations_reorient_pattern() {
+    // .O.
+    // ..O
+    // OOO
+    let glider = glider();
+
+    assert_eq!(
+        alive_cells(&glider.rotate_90()),
+        vec![(0, 0), (1, 0), (1, 2), (2, 0), (2, 1)]
+    );
+    assert_eq!(glider.rotate_90().rotate_90(), glider.rotate_180());
+    assert_eq!(glider.rotate_90().rotate_270(), glider);
+    assert_eq!(
+        alive_cells(&glider.flip_horizontal()),
+        vec![(0, 1), (1, 0), (2, 0), (2, 1), (2, 2)]
+    );
+    assert_eq!(
+        glider.flip_vertical().flip_horizontal(),
+        glider.rotate_180()
+    );
+    assert_eq!(glider.transpose(), glider.rotate_90().flip_horizontal());
+}
+
+#[test]
+fn crop_trims_to_live_cells() {
+    let mut board = Universe::empty(6, 5);
+    board.set_cells(&[(1, 2), (3, 4)]);
+
+    assert_eq!(board.bounding_box(), Some((1, 2, 3, 3)));
+    let cropped = board.crop();
+    assert_eq!((cropped.width(), cropped.height()), (3, 3));
+    assert_eq!(alive_cells(&cropped), vec![(0, 0), (2, 2)]);
+    assert_eq!(Universe::empty(3, 3).crop(), Universe::empty(0, 0));
+}
+
+#[test]
+fn bounding_region_matches_bounding_box() {
+    let mut board = Universe::empty(6, 5);
+    board.set_cells(&[(1, 2), (3, 4)]);
+
+    assert_eq!(
+        board.bounding_region(),
+        Some(Region {
+            row: 1,
+            column: 2,
+            height: 3,
+            width: 3
+        })
+    );
+    assert_eq!(Universe::empty(3, 3).bounding_region(), None);
+}
+
+#[test]
+fn region_edits_stay_inside_selection() {
+    let region = Region::spanning((2, 2), (1, 0));
+    assert_eq!(
+        region,
+        Region {
+            row: 1,
+            column: 0,
+            height: 2,
+            width: 3
+        }
+    );
+
+    let mut board = Universe::empty(4, 4);
+    board.set_cells(&[(0, 0), (1, 1)]);
+
+    let copy = board.copy_region(region);
+    assert_eq!((copy.width(), copy.height()), (3, 2));
+    assert_eq!(alive_cells(&copy), vec![(0, 1)]);
+
+    board.invert_region(region);
+    assert_eq!(
+        alive_cells(&board),
+        vec![(0, 0), (1, 0), (1, 2), (2, 0), (2, 1), (2, 2)]
+    );
+
+    board.fill_region(region, Cell::Dead);
+    assert_eq!(alive_cells(&board), vec![(0, 0)]);
+
+    let mut coin = false;
+    board.randomize_region(region, || {
+        coin = !coin;
+        coin
+    });
+    assert_eq!(alive_cells(&board), vec![(0, 0), (1, 0), (1, 2), (2, 1)]);
+}
+
+#[test]
+fn display_output_parses_back() {
+    let mut universe = glider_on(5, 4);
+    universe.set_cells(&[(3, 4)]);
+    let text = universe.to_string();
+    assert_eq!(text.lines().next(), Some("◻◼◻◻◻"));
+    assert_eq!(text.parse::<Universe>().unwrap(), universe);
+}
+
+#[test]
+fn other_charsets_are_detected() {
+    let glider: Universe = "◻◼◻\n◻◻◼\n◼◼◼\n".parse().unwrap();
+    for text in [".O.\n..O\nOOO", "-#-\n--#\n###\n", " * \n  *\n***\n"].iter() {
+        assert_eq!(text.parse::<Universe>().unwrap(), glider);
+    }
+    assert_eq!(Charset::detect("..\n.."), Some(Charset::DOTS));
+    assert_eq!(Charset::detect("ab"), None);
+}
+
+#[test]
+fn custom_charsets_round_trip() {
+    let charset = Charset {
+        dead: '_',
+        alive: 'x',
+    };
+    let text = "_x_\n__x\nxxx\n";
+    let universe = Universe::from_text(text, charset).unwrap();
+    assert_eq!(
+        alive_cells(&universe),
+        vec![(0, 1), (1, 2), (2, 0), (2, 1), (2, 2)]
+    );
+    assert_eq!(universe.to_text(charset), text);
+    assert_eq!(
+        Universe::from_text("", charset).unwrap(),
+        Universe::empty(0, 0)
+    );
+}
+
+#[test]
+fn ragged_rows_and_stray_characters_are_rejected() {
+    let error = ".O.\n..\nOOO\n".parse::<Universe>().unwrap_err();
+    assert_eq!((error.line, error.column), (2, 3));
+    assert!(error.message.contains("expected 3"), "{}", error);
+
+    let error = ".O.\n.xO\n".parse::<Universe>().unwrap_err();
+    assert_eq!((error.line, error.column), (2, 2));
+}